@@ -0,0 +1,220 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use fancy_regex::Regex;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Version of the on-disk format written by [`BpeTokenizer::save`]
+pub const FORMAT_VERSION: u32 = 1;
+
+/// Number of base tokens, one per byte value
+const BYTE_VOCAB_SIZE: usize = 256;
+
+/// GPT-2 split pattern: contractions, letter runs, digit runs and punctuation
+/// runs each keep one leading space, and trailing whitespace stays on its own
+const GPT2_SPLIT_PATTERN: &str =
+    r"'s|'t|'re|'ve|'m|'ll|'d| ?\p{L}+| ?\p{N}+| ?[^\s\p{L}\p{N}]+|\s+(?!\S)|\s+";
+
+static GPT2_SPLIT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(GPT2_SPLIT_PATTERN).expect("GPT-2 split pattern is valid"));
+
+#[derive(Debug, Error)]
+pub enum BpeError {
+    #[error("Vocabulary size {0} is smaller than the {BYTE_VOCAB_SIZE} base byte tokens")]
+    VocabTooSmall(usize),
+    #[error("Unsupported tokenizer format version {0}, expected {FORMAT_VERSION}")]
+    UnsupportedVersion(u32),
+    #[error("Merge {0:?} refers to a token that does not exist yet")]
+    InvalidMerge((usize, usize)),
+    #[error("Stored vocabulary does not match the merges")]
+    InconsistentVocab,
+    #[error("I/O error: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Invalid tokenizer file: {0}")]
+    Json(#[from] serde_json::Error),
+}
+
+/// Byte-level byte-pair-encoding tokenizer
+pub struct BpeTokenizer {
+    merges: Vec<(usize, usize)>,
+    ranks: HashMap<(usize, usize), usize>,
+    vocab: Vec<Vec<u8>>,
+}
+
+/// Serialized form of a [`BpeTokenizer`]
+#[derive(Serialize, Deserialize)]
+struct BpeFile {
+    version: u32,
+    merges: Vec<(usize, usize)>,
+    vocab: Vec<Vec<u8>>,
+}
+
+impl BpeTokenizer {
+    /// Learns `vocab_size - 256` merges from the bytes of `text`. Pairs are
+    /// only counted within each [`pretokenize`] chunk, so no merge crosses a
+    /// word, number or punctuation boundary. Training stops early once no
+    /// pair of adjacent tokens is left to merge.
+    pub fn train(text: &str, vocab_size: usize) -> Result<Self, BpeError> {
+        if vocab_size < BYTE_VOCAB_SIZE {
+            return Err(BpeError::VocabTooSmall(vocab_size));
+        }
+
+        let mut chunks: Vec<Vec<usize>> = pretokenize(text)
+            .iter()
+            .map(|chunk| chunk.bytes().map(usize::from).collect())
+            .collect();
+        let mut merges = Vec::with_capacity(vocab_size - BYTE_VOCAB_SIZE);
+        for new_id in BYTE_VOCAB_SIZE..vocab_size {
+            // most frequent pair, ties broken by the smallest pair for determinism
+            let Some((&pair, _)) = pair_counts(&chunks)
+                .iter()
+                .max_by(|(pa, ca), (pb, cb)| ca.cmp(cb).then(pb.cmp(pa)))
+            else {
+                break;
+            };
+            for ids in &mut chunks {
+                *ids = merge(ids, pair, new_id);
+            }
+            merges.push(pair);
+        }
+
+        Self::from_merges(merges)
+    }
+
+    /// Rebuilds a tokenizer from an ordered merge list, where the `i`-th merge
+    /// produces token `256 + i`
+    pub fn from_merges(merges: Vec<(usize, usize)>) -> Result<Self, BpeError> {
+        let mut vocab: Vec<Vec<u8>> = (0..=u8::MAX).map(|b| vec![b]).collect();
+        let mut ranks = HashMap::with_capacity(merges.len());
+        for (rank, &(a, b)) in merges.iter().enumerate() {
+            if a >= vocab.len() || b >= vocab.len() {
+                return Err(BpeError::InvalidMerge((a, b)));
+            }
+            let bytes = [vocab[a].as_slice(), vocab[b].as_slice()].concat();
+            vocab.push(bytes);
+            ranks.insert((a, b), rank);
+        }
+
+        Ok(Self {
+            merges,
+            ranks,
+            vocab,
+        })
+    }
+
+    /// Returns the size of the vocabulary, base bytes included
+    pub fn vocab_size(&self) -> usize {
+        self.vocab.len()
+    }
+
+    /// Returns the learned merges in the order they are applied
+    pub fn merges(&self) -> &[(usize, usize)] {
+        &self.merges
+    }
+
+    /// Encodes text to token ids, encoding each [`pretokenize`] chunk on its
+    /// own and concatenating the results
+    pub fn encode(&self, input: &str) -> Vec<usize> {
+        pretokenize(input)
+            .iter()
+            .flat_map(|chunk| self.encode_chunk(chunk))
+            .collect()
+    }
+
+    /// Encodes one chunk by repeatedly applying the earliest learned merge
+    fn encode_chunk(&self, chunk: &str) -> Vec<usize> {
+        let mut ids: Vec<usize> = chunk.bytes().map(usize::from).collect();
+        while ids.len() >= 2 {
+            let Some((pair, rank)) = ids
+                .windows(2)
+                .filter_map(|w| {
+                    self.ranks
+                        .get(&(w[0], w[1]))
+                        .map(|&rank| ((w[0], w[1]), rank))
+                })
+                .min_by_key(|&(_, rank)| rank)
+            else {
+                break;
+            };
+            ids = merge(&ids, pair, BYTE_VOCAB_SIZE + rank);
+        }
+        ids
+    }
+
+    /// Decodes token ids to text, replacing invalid UTF-8 with U+FFFD.
+    /// Panics if any id is outside the vocabulary.
+    pub fn decode(&self, input: &[usize]) -> String {
+        let bytes: Vec<u8> = input
+            .iter()
+            .flat_map(|&id| self.vocab[id].iter().copied())
+            .collect();
+        String::from_utf8_lossy(&bytes).into_owned()
+    }
+
+    /// Writes the merges and vocabulary to a versioned JSON file
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<(), BpeError> {
+        let file = BpeFile {
+            version: FORMAT_VERSION,
+            merges: self.merges.clone(),
+            vocab: self.vocab.clone(),
+        };
+        fs::write(path, serde_json::to_string(&file)?)?;
+        Ok(())
+    }
+
+    /// Loads a tokenizer written by [`save`](Self::save), rejecting files of
+    /// another format version or whose vocabulary disagrees with the merges
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, BpeError> {
+        let file: BpeFile = serde_json::from_str(&fs::read_to_string(path)?)?;
+        if file.version != FORMAT_VERSION {
+            return Err(BpeError::UnsupportedVersion(file.version));
+        }
+        let tokenizer = Self::from_merges(file.merges)?;
+        if tokenizer.vocab != file.vocab {
+            return Err(BpeError::InconsistentVocab);
+        }
+        Ok(tokenizer)
+    }
+}
+
+/// Splits text into the chunks GPT-2 runs BPE on separately, so merges never
+/// cross a word, number or punctuation boundary. Concatenating the chunks
+/// gives back `text`.
+pub fn pretokenize(text: &str) -> Vec<String> {
+    GPT2_SPLIT
+        .find_iter(text)
+        .map(|m| {
+            m.expect("GPT-2 split pattern exceeded the backtrack limit")
+                .as_str()
+                .to_string()
+        })
+        .collect()
+}
+
+/// Counts every pair of adjacent ids within each chunk, never across two
+fn pair_counts(chunks: &[Vec<usize>]) -> HashMap<(usize, usize), usize> {
+    let mut counts = HashMap::new();
+    for pair in chunks.iter().flat_map(|ids| ids.windows(2)) {
+        *counts.entry((pair[0], pair[1])).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// Replaces every occurrence of `pair` in `ids` with `new_id`
+fn merge(ids: &[usize], pair: (usize, usize), new_id: usize) -> Vec<usize> {
+    let mut merged = Vec::with_capacity(ids.len());
+    let mut i = 0;
+    while i < ids.len() {
+        if i + 1 < ids.len() && (ids[i], ids[i + 1]) == pair {
+            merged.push(new_id);
+            i += 2;
+        } else {
+            merged.push(ids[i]);
+            i += 1;
+        }
+    }
+    merged
+}
@@ -0,0 +1,159 @@
+use std::fs::File;
+use std::io::{self, Read};
+use std::path::Path;
+
+use burn::prelude::*;
+use rand::Rng;
+use thiserror::Error;
+
+use crate::tokenizer::{Tokenizer, TokenizerError};
+
+/// Bytes read from disk at a time by `load_corpus_streaming`
+const CHUNK_SIZE: usize = 64 * 1024;
+
+#[derive(Debug, Error)]
+pub enum DataError {
+    #[error(
+        "Sequence of {len} tokens is too short for block size {block_size}, need at least {}",
+        .block_size + 1
+    )]
+    SequenceTooShort { len: usize, block_size: usize },
+    #[error("Failed to read corpus: {0}")]
+    Io(#[from] io::Error),
+    #[error(transparent)]
+    Tokenizer(#[from] TokenizerError),
+}
+
+/// Input and target token indices, each `[batch_size, block_size]`
+pub type Batch<B> = (Tensor<B, 2, Int>, Tensor<B, 2, Int>);
+
+/// Samples `batch_size` random contexts of `block_size` tokens from `data`,
+/// returning inputs `[batch_size, block_size]` and the targets shifted one token ahead
+pub fn get_batch<B: Backend>(
+    data: &Tensor<B, 1, Int>,
+    block_size: usize,
+    batch_size: usize,
+    rng: &mut impl Rng,
+) -> Result<Batch<B>, DataError> {
+    let [len] = data.dims();
+    if len < block_size + 1 {
+        return Err(DataError::SequenceTooShort { len, block_size });
+    }
+
+    let offsets: Vec<usize> = (0..batch_size)
+        .map(|_| rng.random_range(0..len - block_size))
+        .collect();
+    let x = offsets
+        .iter()
+        .map(|&i| data.clone().slice(i..i + block_size))
+        .collect();
+    let y = offsets
+        .iter()
+        .map(|&i| data.clone().slice(i + 1..i + block_size + 1))
+        .collect();
+
+    Ok((Tensor::stack(x, 0), Tensor::stack(y, 0)))
+}
+
+/// Encoded token sequence viewed as overlapping `block_size` context/target pairs
+#[derive(Debug, Clone)]
+pub struct TextDataset<B: Backend> {
+    data: Tensor<B, 1, Int>,
+    block_size: usize,
+}
+
+impl<B: Backend> TextDataset<B> {
+    pub fn new(data: Tensor<B, 1, Int>, block_size: usize) -> Result<Self, DataError> {
+        let [len] = data.dims();
+        if len < block_size + 1 {
+            return Err(DataError::SequenceTooShort { len, block_size });
+        }
+        Ok(Self { data, block_size })
+    }
+
+    pub fn block_size(&self) -> usize {
+        self.block_size
+    }
+
+    /// Number of context/target pairs, one per starting offset
+    pub fn len(&self) -> usize {
+        let [len] = self.data.dims();
+        len - self.block_size
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the `i`-th context and its targets shifted one token ahead,
+    /// each `[block_size]`. Panics if `i >= len()`.
+    pub fn get(&self, i: usize) -> (Tensor<B, 1, Int>, Tensor<B, 1, Int>) {
+        assert!(
+            i < self.len(),
+            "index {i} out of range for dataset of length {}",
+            self.len()
+        );
+        let x = self.data.clone().slice(i..i + self.block_size);
+        let y = self.data.clone().slice(i + 1..i + self.block_size + 1);
+        (x, y)
+    }
+
+    /// Iterates over every pair in order, stacked into batches of
+    /// `batch_size`. The last batch is smaller when `len()` is not a multiple.
+    pub fn iter_batches(&self, batch_size: usize) -> impl Iterator<Item = Batch<B>> + '_ {
+        assert!(batch_size > 0, "batch_size must be positive");
+        (0..self.len()).step_by(batch_size).map(move |start| {
+            let end = (start + batch_size).min(self.len());
+            let (x, y): (Vec<_>, Vec<_>) = (start..end).map(|i| self.get(i)).unzip();
+            (Tensor::stack(x, 0), Tensor::stack(y, 0))
+        })
+    }
+}
+
+/// Reads and encodes the corpus at `path` in fixed-size chunks, so the file is
+/// never held in memory as a `String` alongside its tokens. Produces the same
+/// tensor as encoding the whole file at once.
+pub fn load_corpus_streaming<B: Backend>(
+    path: impl AsRef<Path>,
+    tokenizer: &Tokenizer,
+    device: &B::Device,
+) -> Result<Tensor<B, 1, Int>, DataError> {
+    let mut file = File::open(path)?;
+    let mut chunk = vec![0u8; CHUNK_SIZE];
+    // bytes of a character split across two chunks
+    let mut pending: Vec<u8> = Vec::new();
+    let mut tokens: Vec<i64> = Vec::new();
+
+    loop {
+        let n = file.read(&mut chunk)?;
+        if n == 0 {
+            break;
+        }
+        pending.extend_from_slice(&chunk[..n]);
+
+        let valid = match std::str::from_utf8(&pending) {
+            Ok(text) => text.len(),
+            // the chunk ends partway through a character
+            Err(e) if e.error_len().is_none() => e.valid_up_to(),
+            Err(e) => return Err(io::Error::new(io::ErrorKind::InvalidData, e).into()),
+        };
+        let text = std::str::from_utf8(&pending[..valid]).expect("prefix was just validated");
+        for c in text.chars() {
+            let idx = tokenizer
+                .index_of(c)
+                .ok_or(TokenizerError::CharacterNotInVocabulary(c))?;
+            tokens.push(idx as i64);
+        }
+        pending.drain(..valid);
+    }
+
+    if !pending.is_empty() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "corpus ends partway through a UTF-8 character",
+        )
+        .into());
+    }
+    let len = tokens.len();
+    Ok(Tensor::from_data(TensorData::new(tokens, [len]), device))
+}
@@ -0,0 +1,36 @@
+use burn::prelude::*;
+use micrograd::engine::Value;
+use micrograd::nn::MLP;
+
+/// Converts a `[batch, features]` tensor into one row of fresh leaf `Value`s per example
+pub fn tensor_to_values<B: Backend>(tensor: Tensor<B, 2>) -> Vec<Vec<Value>> {
+    let [_, features] = tensor.dims();
+    let data: Vec<f64> = tensor.into_data().iter::<f64>().collect();
+    data.chunks(features.max(1))
+        .map(|row| row.iter().map(|&x| Value::new(x)).collect())
+        .collect()
+}
+
+/// Stacks the data of equally long rows of `Value`s into a `[rows, len]` tensor.
+/// Panics if the rows have different lengths.
+pub fn values_to_tensor<B: Backend>(rows: &[Vec<Value>], device: &B::Device) -> Tensor<B, 2> {
+    let len = rows.first().map_or(0, Vec::len);
+    assert!(
+        rows.iter().all(|row| row.len() == len),
+        "Expected all rows to have {} values",
+        len
+    );
+    let data: Vec<f32> = rows.iter().flatten().map(|v| v.data() as f32).collect();
+    Tensor::from_data(TensorData::new(data, [rows.len(), len]), device)
+}
+
+/// Runs `mlp` on every row of a `[batch, nin]` tensor, returning `[batch, nout]`.
+/// Only the data crosses over; no gradient flows back into the tensor.
+pub fn mlp_forward_tensor<B: Backend>(mlp: &MLP, x: Tensor<B, 2>) -> Tensor<B, 2> {
+    let device = x.device();
+    let outputs: Vec<Vec<Value>> = tensor_to_values(x)
+        .iter()
+        .map(|row| mlp.forward(row))
+        .collect();
+    values_to_tensor(&outputs, &device)
+}
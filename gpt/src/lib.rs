@@ -0,0 +1,9 @@
+pub mod bpe;
+pub mod data;
+pub mod interop;
+pub mod model;
+pub mod sampling;
+pub mod schedule;
+pub mod tokenizer;
+pub mod train;
+pub mod transformer;
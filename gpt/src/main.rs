@@ -3,9 +3,9 @@ use std::fs;
 use burn::backend::Wgpu;
 use burn::tensor::{Int, Tensor};
 
-use crate::tokenizer::Tokenizer;
-
-mod tokenizer;
+use gpt::model::{BigramLanguageModel, LanguageModel};
+use gpt::sampling::GenerationConfig;
+use gpt::tokenizer::Tokenizer;
 
 type Backend = Wgpu;
 
@@ -38,4 +38,18 @@ fn main() {
     println!("Shape: {:?}", data.shape());
     println!("Data type: {:?}", data.dtype());
     println!("First 10 tokens: {}", data.slice(..10));
+
+    println!("\n=== Generation (untrained bigram) ===");
+    let model = BigramLanguageModel::<Backend>::new(vocab_size, &device);
+    let generated = model
+        .generate_from_prompt(
+            "",
+            &shakespeare_tokenizer,
+            100,
+            &GenerationConfig::default(),
+            &device,
+            &mut rand::rng(),
+        )
+        .expect("Generated tokens should be in the vocabulary");
+    println!("{}", generated);
 }
@@ -0,0 +1,353 @@
+use burn::nn::{Embedding, EmbeddingConfig, Linear, LinearConfig};
+use burn::prelude::*;
+use rand::Rng;
+
+use crate::sampling::{GenerationConfig, apply_repetition_penalty, sample_next};
+use crate::tokenizer::{Tokenizer, TokenizerError};
+use crate::transformer::{Activation, Block, BlockConfig, Norm, Normalization};
+
+/// A model that predicts next-token logits from a sequence of token indices
+pub trait LanguageModel<B: Backend> {
+    /// Maps token indices `[batch, time]` to logits `[batch, time, vocab_size]`
+    fn forward(&self, idx: Tensor<B, 2, Int>) -> Tensor<B, 3>;
+
+    /// Longest context the model can condition on, unlimited by default
+    fn block_size(&self) -> usize {
+        usize::MAX
+    }
+
+    /// Extends every sequence in `idx` by up to `max_new_tokens` sampled tokens.
+    ///
+    /// With `config.eos_id` set, a sequence is finished once it samples that
+    /// token and generation stops as soon as every sequence has finished, so a
+    /// single sequence ends with the eos token. Sequences that finish earlier
+    /// than others in the batch are padded with the eos token.
+    ///
+    /// Tokens are drawn from `rng`, so a seeded RNG reproduces the same
+    /// sequences. Greedy decoding (temperature `0.0`) never consumes it.
+    fn generate(
+        &self,
+        idx: Tensor<B, 2, Int>,
+        max_new_tokens: usize,
+        config: &GenerationConfig,
+        rng: &mut impl Rng,
+    ) -> Tensor<B, 2, Int> {
+        let device = idx.device();
+        let mut idx = idx;
+        let mut finished = vec![false; idx.dims()[0]];
+
+        for _ in 0..max_new_tokens {
+            let [batch, time] = idx.dims();
+            // crop the context to the last block_size tokens
+            let start = time.saturating_sub(self.block_size());
+            let logits = self.forward(idx.clone().slice([0..batch, start..time]));
+
+            // focus only on the last time step
+            let [_, time, vocab_size] = logits.dims();
+            let mut logits = logits
+                .slice([0..batch, time - 1..time, 0..vocab_size])
+                .reshape([batch, vocab_size])
+                .into_data()
+                .iter::<f64>()
+                .collect::<Vec<_>>();
+
+            if config.repetition_penalty != 1.0 {
+                // penalize every token of the sequence so far, prompt included
+                let tokens: Vec<usize> = idx.to_data().iter::<i64>().map(|t| t as usize).collect();
+                let len = idx.dims()[1];
+                for (row, previous) in logits.chunks_mut(vocab_size).zip(tokens.chunks(len)) {
+                    apply_repetition_penalty(row, previous, config.repetition_penalty);
+                }
+            }
+
+            let next: Vec<i64> = logits
+                .chunks(vocab_size)
+                .zip(finished.iter_mut())
+                .map(|(row, done)| match config.eos_id {
+                    Some(eos) if *done => eos as i64,
+                    Some(eos) => {
+                        let token = sample_next(row, config, rng);
+                        *done = token == eos;
+                        token as i64
+                    }
+                    None => sample_next(row, config, rng) as i64,
+                })
+                .collect();
+            let next = Tensor::<B, 1, Int>::from_data(TensorData::new(next, [batch]), &device)
+                .reshape([batch, 1]);
+            idx = Tensor::cat(vec![idx, next], 1);
+
+            if finished.iter().all(|&done| done) {
+                break;
+            }
+        }
+
+        idx
+    }
+
+    /// Encodes `prompt`, extends it by `max_new_tokens` and decodes the whole
+    /// sequence, prompt included. An empty prompt starts from token 0.
+    fn generate_from_prompt(
+        &self,
+        prompt: &str,
+        tokenizer: &Tokenizer,
+        max_new_tokens: usize,
+        config: &GenerationConfig,
+        device: &B::Device,
+        rng: &mut impl Rng,
+    ) -> Result<String, TokenizerError> {
+        let mut tokens: Vec<i64> = tokenizer
+            .try_encode(prompt)?
+            .into_iter()
+            .map(|t| t as i64)
+            .collect();
+        if tokens.is_empty() {
+            tokens.push(0);
+        }
+
+        let len = tokens.len();
+        let idx = Tensor::<B, 2, Int>::from_data(TensorData::new(tokens, [1, len]), device);
+        let generated: Vec<usize> = self
+            .generate(idx, max_new_tokens, config, rng)
+            .into_data()
+            .iter::<i64>()
+            .map(|t| t as usize)
+            .collect();
+        tokenizer.try_decode(&generated)
+    }
+}
+
+/// Predicts the next token from the current token alone via a lookup table of logits
+#[derive(Module, Debug)]
+pub struct BigramLanguageModel<B: Backend> {
+    token_embedding_table: Embedding<B>,
+}
+
+impl<B: Backend> BigramLanguageModel<B> {
+    pub fn new(vocab_size: usize, device: &B::Device) -> Self {
+        Self {
+            token_embedding_table: EmbeddingConfig::new(vocab_size, vocab_size).init(device),
+        }
+    }
+}
+
+impl<B: Backend> LanguageModel<B> for BigramLanguageModel<B> {
+    fn forward(&self, idx: Tensor<B, 2, Int>) -> Tensor<B, 3> {
+        self.token_embedding_table.forward(idx)
+    }
+}
+
+/// The count-based bigram model from the first makemore lecture, a baseline
+/// for [`BigramLanguageModel`]: the probability of the next token is the
+/// relative frequency with which it followed the current one in training.
+/// Nothing is learned by gradient descent.
+#[derive(Debug, Clone)]
+pub struct CountBigramModel {
+    /// `counts[i][j]` is the number of times token `j` followed token `i`
+    counts: Vec<Vec<usize>>,
+    /// Rows of `counts` plus the smoothing, normalized to sum to 1
+    probs: Vec<Vec<f64>>,
+}
+
+impl CountBigramModel {
+    /// Counts every consecutive pair of `tokens`, adding `smoothing` to every
+    /// count before normalizing so unseen pairs keep some probability. A row
+    /// with no counts at all becomes uniform.
+    pub fn new(tokens: &[usize], vocab_size: usize, smoothing: f64) -> Self {
+        let mut counts = vec![vec![0; vocab_size]; vocab_size];
+        for pair in tokens.windows(2) {
+            counts[pair[0]][pair[1]] += 1;
+        }
+        let probs = counts
+            .iter()
+            .map(|row| {
+                let total = row.iter().sum::<usize>() as f64 + smoothing * vocab_size as f64;
+                row.iter()
+                    .map(|&c| {
+                        if total > 0.0 {
+                            (c as f64 + smoothing) / total
+                        } else {
+                            1.0 / vocab_size as f64
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        Self { counts, probs }
+    }
+
+    pub fn counts(&self) -> &[Vec<usize>] {
+        &self.counts
+    }
+
+    /// Next-token distribution for each current token
+    pub fn probs(&self) -> &[Vec<f64>] {
+        &self.probs
+    }
+}
+
+/// The logits are the log-probabilities, so sampling with the default
+/// `GenerationConfig` draws from the counted distributions directly
+impl<B: Backend> LanguageModel<B> for CountBigramModel {
+    fn forward(&self, idx: Tensor<B, 2, Int>) -> Tensor<B, 3> {
+        let [batch, time] = idx.dims();
+        let vocab_size = self.probs.len();
+        let log_probs: Vec<f32> = self
+            .probs
+            .iter()
+            .flatten()
+            .map(|&p| p.ln() as f32)
+            .collect();
+        let table = Tensor::<B, 2>::from_data(
+            TensorData::new(log_probs, [vocab_size, vocab_size]),
+            &idx.device(),
+        );
+        table
+            .select(0, idx.reshape([batch * time]))
+            .reshape([batch, time, vocab_size])
+    }
+}
+
+/// How token positions are encoded before entering the transformer
+#[derive(Config, Debug, PartialEq, Eq)]
+pub enum PositionalEncoding {
+    /// A trained embedding table with one vector per position
+    Learned,
+    /// The fixed sin/cos table from "Attention Is All You Need"
+    Sinusoidal,
+}
+
+#[derive(Config, Debug)]
+pub struct GptConfig {
+    pub vocab_size: usize,
+    #[config(default = 256)]
+    pub block_size: usize,
+    #[config(default = 384)]
+    pub n_embd: usize,
+    #[config(default = 6)]
+    pub n_head: usize,
+    /// Number of transformer blocks applied in sequence
+    #[config(default = 6)]
+    pub n_layer: usize,
+    #[config(default = 0.2)]
+    pub dropout: f64,
+    #[config(default = "PositionalEncoding::Learned")]
+    pub positional_encoding: PositionalEncoding,
+    /// Feed-forward nonlinearity
+    #[config(default = "Activation::ReLU")]
+    pub activation: Activation,
+    /// Normalization inside each block and before the output projection
+    #[config(default = "Normalization::LayerNorm")]
+    pub norm: Normalization,
+    /// Reuse the token embedding table (transposed) as the output projection, as in GPT-2
+    #[config(default = false)]
+    pub tie_weights: bool,
+}
+
+impl GptConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> GptModel<B> {
+        let position_embedding_table = match self.positional_encoding {
+            PositionalEncoding::Learned => {
+                Some(EmbeddingConfig::new(self.block_size, self.n_embd).init(device))
+            }
+            PositionalEncoding::Sinusoidal => None,
+        };
+        let block = BlockConfig::new(self.n_embd, self.n_head)
+            .with_dropout(self.dropout)
+            .with_activation(self.activation.clone())
+            .with_norm(self.norm.clone());
+        GptModel {
+            token_embedding_table: EmbeddingConfig::new(self.vocab_size, self.n_embd).init(device),
+            position_embedding_table,
+            blocks: (0..self.n_layer).map(|_| block.init(device)).collect(),
+            ln_f: self.norm.init(self.n_embd, device),
+            lm_head: (!self.tie_weights)
+                .then(|| LinearConfig::new(self.n_embd, self.vocab_size).init(device)),
+            block_size: self.block_size,
+            n_embd: self.n_embd,
+        }
+    }
+}
+
+/// Decoder-only transformer language model
+#[derive(Module, Debug)]
+pub struct GptModel<B: Backend> {
+    token_embedding_table: Embedding<B>,
+    /// `None` when positions use the fixed sinusoidal encoding
+    position_embedding_table: Option<Embedding<B>>,
+    blocks: Vec<Block<B>>,
+    /// Final normalization of the last block's output
+    ln_f: Norm<B>,
+    /// `None` when the output projection is tied to the token embedding table
+    lm_head: Option<Linear<B>>,
+    block_size: usize,
+    n_embd: usize,
+}
+
+impl<B: Backend> GptModel<B> {
+    /// Token embedding table `[vocab_size, n_embd]`
+    pub fn token_embedding_weight(&self) -> Tensor<B, 2> {
+        self.token_embedding_table.weight.val()
+    }
+
+    /// Output projection `[n_embd, vocab_size]` mapping hidden states to logits;
+    /// the transposed token embedding table when weights are tied
+    pub fn lm_head_weight(&self) -> Tensor<B, 2> {
+        match &self.lm_head {
+            Some(lm_head) => lm_head.weight.val(),
+            None => self.token_embedding_weight().transpose(),
+        }
+    }
+}
+
+impl<B: Backend> LanguageModel<B> for GptModel<B> {
+    fn forward(&self, idx: Tensor<B, 2, Int>) -> Tensor<B, 3> {
+        let [_, time] = idx.dims();
+        let device = idx.device();
+
+        let tok_emb = self.token_embedding_table.forward(idx);
+        let pos_emb = match &self.position_embedding_table {
+            Some(table) => {
+                table.forward(Tensor::<B, 1, Int>::arange(0..time as i64, &device).unsqueeze())
+            }
+            None => sinusoidal_encoding::<B>(time, self.n_embd, &device).unsqueeze(),
+        };
+
+        let x = self
+            .blocks
+            .iter()
+            .fold(tok_emb + pos_emb, |x, block| block.forward(x));
+        let x = self.ln_f.forward(x);
+        match &self.lm_head {
+            Some(lm_head) => lm_head.forward(x),
+            None => x.matmul(self.lm_head_weight().unsqueeze()),
+        }
+    }
+
+    fn block_size(&self) -> usize {
+        self.block_size
+    }
+}
+
+/// Fixed positional encoding `[block_size, n_embd]` where even columns hold
+/// `sin(pos / 10000^(2i / n_embd))` and odd columns the matching cosine
+pub fn sinusoidal_encoding<B: Backend>(
+    block_size: usize,
+    n_embd: usize,
+    device: &B::Device,
+) -> Tensor<B, 2> {
+    let table: Vec<f32> = (0..block_size)
+        .flat_map(|pos| {
+            (0..n_embd).map(move |dim| {
+                let pair = (dim / 2 * 2) as f64;
+                let angle = pos as f64 / 10000_f64.powf(pair / n_embd as f64);
+                if dim % 2 == 0 {
+                    angle.sin() as f32
+                } else {
+                    angle.cos() as f32
+                }
+            })
+        })
+        .collect();
+    Tensor::from_data(TensorData::new(table, [block_size, n_embd]), device)
+}
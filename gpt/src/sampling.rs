@@ -0,0 +1,140 @@
+use rand::Rng;
+use rand::distr::Distribution;
+use rand::distr::weighted::WeightedIndex;
+
+/// Controls how the next token is picked from the model's logits
+#[derive(Debug, Clone, Copy)]
+pub struct GenerationConfig {
+    /// Softmax temperature, `0.0` selects the most likely token (greedy decoding)
+    pub temperature: f64,
+    /// Keeps only the `k` most likely tokens before sampling
+    pub top_k: Option<usize>,
+    /// Nucleus sampling: keeps the smallest set of tokens whose cumulative probability exceeds `p`
+    pub top_p: Option<f64>,
+    /// End-of-sequence token: generation stops once every sequence has sampled it
+    pub eos_id: Option<usize>,
+    /// Penalizes tokens already in the sequence, see `apply_repetition_penalty`.
+    /// `1.0` disables it.
+    pub repetition_penalty: f64,
+}
+
+impl Default for GenerationConfig {
+    fn default() -> Self {
+        Self {
+            temperature: 1.0,
+            top_k: None,
+            top_p: None,
+            eos_id: None,
+            repetition_penalty: 1.0,
+        }
+    }
+}
+
+/// Converts logits to probabilities, dividing them by `temperature` first
+pub fn softmax(logits: &[f64], temperature: f64) -> Vec<f64> {
+    let max = logits.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+    let exps: Vec<f64> = logits
+        .iter()
+        .map(|&l| ((l - max) / temperature).exp())
+        .collect();
+    let sum: f64 = exps.iter().sum();
+    exps.into_iter().map(|e| e / sum).collect()
+}
+
+/// Zeroes every probability outside the `k` most likely tokens and renormalizes
+pub fn top_k_filter(probs: &mut [f64], k: usize) {
+    if k == 0 || k >= probs.len() {
+        return;
+    }
+    let order = sorted_indices(probs);
+    for &idx in &order[k..] {
+        probs[idx] = 0.0;
+    }
+    renormalize(probs);
+}
+
+/// Zeroes every probability outside the nucleus (the smallest set of most
+/// likely tokens whose cumulative probability reaches `p`) and renormalizes.
+/// A `p` of `1.0` or more keeps the full distribution.
+pub fn top_p_filter(probs: &mut [f64], p: f64) {
+    if p >= 1.0 {
+        return;
+    }
+    let order = sorted_indices(probs);
+    let mut cumulative = 0.0;
+    let mut keep = order.len();
+    for (rank, &idx) in order.iter().enumerate() {
+        cumulative += probs[idx];
+        if cumulative >= p {
+            keep = rank + 1;
+            break;
+        }
+    }
+    for &idx in &order[keep..] {
+        probs[idx] = 0.0;
+    }
+    renormalize(probs);
+}
+
+/// Makes every token in `previous` less likely, as in Hugging Face's
+/// implementation: positive logits are divided by `penalty` and negative ones
+/// multiplied by it, so a penalty above `1.0` always lowers them. Each token
+/// is penalized once, however often it occurred.
+pub fn apply_repetition_penalty(logits: &mut [f64], previous: &[usize], penalty: f64) {
+    let mut seen = vec![false; logits.len()];
+    for &token in previous {
+        if token < logits.len() && !std::mem::replace(&mut seen[token], true) {
+            let logit = &mut logits[token];
+            *logit = if *logit > 0.0 {
+                *logit / penalty
+            } else {
+                *logit * penalty
+            };
+        }
+    }
+}
+
+/// Draws a token index from the given probability distribution
+pub fn sample(probs: &[f64], rng: &mut impl Rng) -> usize {
+    WeightedIndex::new(probs)
+        .expect("Probabilities must be non-negative and not all zero")
+        .sample(rng)
+}
+
+/// Picks the next token from raw logits according to `config`
+pub fn sample_next(logits: &[f64], config: &GenerationConfig, rng: &mut impl Rng) -> usize {
+    if config.temperature <= 0.0 {
+        return argmax(logits);
+    }
+    let mut probs = softmax(logits, config.temperature);
+    if let Some(k) = config.top_k {
+        top_k_filter(&mut probs, k);
+    }
+    if let Some(p) = config.top_p {
+        top_p_filter(&mut probs, p);
+    }
+    sample(&probs, rng)
+}
+
+fn argmax(values: &[f64]) -> usize {
+    values
+        .iter()
+        .enumerate()
+        .max_by(|a, b| a.1.total_cmp(b.1))
+        .map(|(idx, _)| idx)
+        .expect("Cannot pick a token from empty logits")
+}
+
+/// Token indices ordered from most to least likely
+fn sorted_indices(probs: &[f64]) -> Vec<usize> {
+    let mut order: Vec<usize> = (0..probs.len()).collect();
+    order.sort_by(|&a, &b| probs[b].total_cmp(&probs[a]));
+    order
+}
+
+fn renormalize(probs: &mut [f64]) {
+    let sum: f64 = probs.iter().sum();
+    for p in probs.iter_mut() {
+        *p /= sum;
+    }
+}
@@ -0,0 +1,61 @@
+use burn::lr_scheduler::LrScheduler;
+use burn::optim::LearningRate;
+use burn::tensor::backend::Backend;
+
+/// Linear warmup from 0 to `peak_lr` over `warmup_steps`, followed by a linear
+/// decay back to 0 at `total_steps`, as commonly used to train transformers.
+#[derive(Debug, Clone, Copy)]
+pub struct WarmupLinear {
+    warmup_steps: usize,
+    total_steps: usize,
+    peak_lr: LearningRate,
+    step: usize,
+}
+
+impl WarmupLinear {
+    pub fn new(warmup_steps: usize, total_steps: usize, peak_lr: LearningRate) -> Self {
+        assert!(
+            warmup_steps <= total_steps,
+            "Warmup steps ({}) must not exceed total steps ({})",
+            warmup_steps,
+            total_steps
+        );
+        Self {
+            warmup_steps,
+            total_steps,
+            peak_lr,
+            step: 0,
+        }
+    }
+
+    /// Learning rate at the given step, independent of the scheduler state
+    pub fn lr_at(&self, step: usize) -> LearningRate {
+        if step < self.warmup_steps {
+            self.peak_lr * step as f64 / self.warmup_steps as f64
+        } else if step < self.total_steps {
+            let remaining = (self.total_steps - step) as f64;
+            self.peak_lr * remaining / (self.total_steps - self.warmup_steps) as f64
+        } else {
+            0.0
+        }
+    }
+}
+
+impl LrScheduler for WarmupLinear {
+    type Record<B: Backend> = usize;
+
+    fn step(&mut self) -> LearningRate {
+        let lr = self.lr_at(self.step);
+        self.step += 1;
+        lr
+    }
+
+    fn to_record<B: Backend>(&self) -> Self::Record<B> {
+        self.step
+    }
+
+    fn load_record<B: Backend>(mut self, record: Self::Record<B>) -> Self {
+        self.step = record;
+        self
+    }
+}
@@ -1,5 +1,6 @@
 use std::collections::{BTreeSet, HashMap};
 
+use rand::Rng;
 use thiserror::Error;
 
 #[derive(Debug, Error)]
@@ -8,6 +9,8 @@ pub enum TokenizerError {
     CharacterNotInVocabulary(char),
     #[error("Index {0} out of bounds for vocabulary")]
     IndexOutOfBounds(usize),
+    #[error("Character '{0}' appears more than once in the vocabulary")]
+    DuplicateCharacter(char),
 }
 
 pub struct Tokenizer {
@@ -19,8 +22,35 @@ impl Tokenizer {
     /// Creates a new tokenizer from the given text, building vocabulary from unique characters
     pub fn new(text: &str) -> Self {
         let chars: BTreeSet<char> = text.chars().collect();
-        let index_to_char: Vec<char> = chars.iter().copied().collect();
+        Self::from_ordered(chars.into_iter().collect())
+    }
+
+    /// Creates a tokenizer whose ids follow descending character frequency in
+    /// `text`, so id 0 is the most common character and truncating the
+    /// vocabulary keeps the common ones. Ties are broken by code point.
+    pub fn new_by_frequency(text: &str) -> Self {
+        let mut counts: HashMap<char, usize> = HashMap::new();
+        for c in text.chars() {
+            *counts.entry(c).or_insert(0) += 1;
+        }
+        let mut chars: Vec<(char, usize)> = counts.into_iter().collect();
+        chars.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Self::from_ordered(chars.into_iter().map(|(c, _)| c).collect())
+    }
+
+    /// Creates a tokenizer from a predefined vocabulary, giving `chars[i]` id
+    /// `i`, e.g. to match the id layout of a pretrained model. Fails if a
+    /// character appears twice.
+    pub fn from_vocab(chars: &[char]) -> Result<Self, TokenizerError> {
+        let mut seen = BTreeSet::new();
+        if let Some(&c) = chars.iter().find(|&&c| !seen.insert(c)) {
+            return Err(TokenizerError::DuplicateCharacter(c));
+        }
+        Ok(Self::from_ordered(chars.to_vec()))
+    }
 
+    /// Tokenizer mapping `index_to_char[i]` to id `i`
+    fn from_ordered(index_to_char: Vec<char>) -> Self {
         let char_to_index: HashMap<char, usize> = index_to_char
             .iter()
             .enumerate()
@@ -33,6 +63,20 @@ impl Tokenizer {
         }
     }
 
+    /// Appends characters missing from the vocabulary, giving them the next
+    /// free indices in order. Existing ids are left untouched, so a model
+    /// trained on the old vocabulary stays valid; duplicates are ignored.
+    ///
+    /// Extended characters are no longer in sorted id order.
+    pub fn extend(&mut self, chars: &[char]) {
+        for &c in chars {
+            if !self.char_to_index.contains_key(&c) {
+                self.char_to_index.insert(c, self.index_to_char.len());
+                self.index_to_char.push(c);
+            }
+        }
+    }
+
     /// Returns the vocabulary as a sorted set of characters
     pub fn get_vocab(&self) -> BTreeSet<char> {
         self.index_to_char.iter().copied().collect()
@@ -55,14 +99,36 @@ impl Tokenizer {
             .unwrap_or_else(|e| panic!("Decoding failed: {}", e))
     }
 
+    /// Decodes indices to text, replacing out-of-bounds indices with U+FFFD instead of failing
+    pub fn decode_lossy(&self, input: &[usize]) -> String {
+        input
+            .iter()
+            .map(|&idx| self.decode_char(idx).unwrap_or(char::REPLACEMENT_CHARACTER))
+            .collect()
+    }
+
+    /// Decodes a single index to its character without allocating a `String`
+    pub fn decode_char(&self, idx: usize) -> Result<char, TokenizerError> {
+        self.char_of(idx)
+            .ok_or(TokenizerError::IndexOutOfBounds(idx))
+    }
+
+    /// Returns the index of a single character, or `None` if it is not in vocabulary
+    pub fn index_of(&self, c: char) -> Option<usize> {
+        self.char_to_index.get(&c).copied()
+    }
+
+    /// Returns the character at a single index, or `None` if it is out of bounds
+    pub fn char_of(&self, idx: usize) -> Option<char> {
+        self.index_to_char.get(idx).copied()
+    }
+
     /// Encodes input text to indices, returning an error if any character is not in vocabulary
     pub fn try_encode(&self, input: &str) -> Result<Vec<usize>, TokenizerError> {
         input
             .chars()
             .map(|c| {
-                self.char_to_index
-                    .get(&c)
-                    .copied()
+                self.index_of(c)
                     .ok_or(TokenizerError::CharacterNotInVocabulary(c))
             })
             .collect()
@@ -70,14 +136,75 @@ impl Tokenizer {
 
     /// Decodes indices to text, returning an error if any index is out of bounds
     pub fn try_decode(&self, input: &[usize]) -> Result<String, TokenizerError> {
-        input
+        input.iter().map(|&idx| self.decode_char(idx)).collect()
+    }
+
+    /// Encodes each input, returning the first error if any character is not in vocabulary
+    pub fn encode_batch(&self, inputs: &[&str]) -> Result<Vec<Vec<usize>>, TokenizerError> {
+        inputs.iter().map(|input| self.try_encode(input)).collect()
+    }
+
+    /// Encodes each input and right-pads with `pad_id` to the longest sequence,
+    /// panics if any character is not in vocabulary
+    pub fn encode_batch_padded(&self, inputs: &[&str], pad_id: usize) -> Vec<Vec<usize>> {
+        self.encode_batch_with_mask(inputs, pad_id).0
+    }
+
+    /// Like `encode_batch_padded`, also returning an attention mask of the same
+    /// shape that is `true` at real tokens and `false` at padding. The mask
+    /// comes from the sequence lengths, so it stays correct even when `pad_id`
+    /// is also a real token.
+    pub fn encode_batch_with_mask(
+        &self,
+        inputs: &[&str],
+        pad_id: usize,
+    ) -> (Vec<Vec<usize>>, Vec<Vec<bool>>) {
+        let mut batch: Vec<Vec<usize>> = inputs.iter().map(|input| self.encode(input)).collect();
+        let max_len = batch.iter().map(Vec::len).max().unwrap_or(0);
+        let mask = batch
             .iter()
-            .map(|&idx| {
-                self.index_to_char
-                    .get(idx)
-                    .copied()
-                    .ok_or(TokenizerError::IndexOutOfBounds(idx))
-            })
-            .collect()
+            .map(|seq| (0..max_len).map(|i| i < seq.len()).collect())
+            .collect();
+        for seq in &mut batch {
+            seq.resize(max_len, pad_id);
+        }
+        (batch, mask)
+    }
+}
+
+/// Returns whether `text` survives an encode/decode round trip through `tok`,
+/// or `false` if it contains a character outside the vocabulary
+pub fn check_roundtrip(tok: &Tokenizer, text: &str) -> bool {
+    tok.try_encode(text)
+        .and_then(|encoded| tok.try_decode(&encoded))
+        .is_ok_and(|decoded| decoded == text)
+}
+
+/// Swaps each position with its successor with probability `p`, for
+/// character-level noise in training data. A swapped pair is not considered
+/// again, so with `p = 1.0` every position of an even-length sequence moves.
+///
+/// Panics if `p` is not in `[0, 1]`.
+pub fn augment_swap(ids: &[usize], p: f64, rng: &mut impl Rng) -> Vec<usize> {
+    let mut out = ids.to_vec();
+    let mut i = 0;
+    while i + 1 < out.len() {
+        if rng.random_bool(p) {
+            out.swap(i, i + 1);
+            i += 2;
+        } else {
+            i += 1;
+        }
     }
+    out
+}
+
+/// Drops each position independently with probability `p`
+///
+/// Panics if `p` is not in `[0, 1]`.
+pub fn augment_drop(ids: &[usize], p: f64, rng: &mut impl Rng) -> Vec<usize> {
+    ids.iter()
+        .copied()
+        .filter(|_| !rng.random_bool(p))
+        .collect()
 }
@@ -0,0 +1,159 @@
+use std::fs::{File, OpenOptions};
+use std::io::{BufWriter, Write};
+use std::path::Path;
+
+use burn::lr_scheduler::LrScheduler;
+use burn::module::AutodiffModule;
+use burn::nn::loss::CrossEntropyLossConfig;
+use burn::optim::{GradientsParams, Optimizer};
+use burn::prelude::*;
+use burn::tensor::backend::AutodiffBackend;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::data::{DataError, get_batch};
+use crate::model::LanguageModel;
+
+#[derive(Debug, Error)]
+pub enum TrainError {
+    #[error(transparent)]
+    Data(#[from] DataError),
+    #[error("Failed to write loss log: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize loss record: {0}")]
+    Json(#[from] serde_json::Error),
+    #[error("eval_interval must be at least 1")]
+    ZeroEvalInterval,
+}
+
+#[derive(Config, Debug)]
+pub struct TrainingConfig {
+    #[config(default = 5000)]
+    pub max_iters: usize,
+    /// Steps between loss evaluations; the last step is always evaluated
+    #[config(default = 500)]
+    pub eval_interval: usize,
+    /// Batches averaged per split when estimating the loss
+    #[config(default = 200)]
+    pub eval_iters: usize,
+    #[config(default = 64)]
+    pub batch_size: usize,
+    #[config(default = 256)]
+    pub block_size: usize,
+}
+
+/// Losses estimated at one evaluation step
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct LossRecord {
+    pub step: usize,
+    pub train_loss: f64,
+    pub val_loss: f64,
+    pub lr: f64,
+}
+
+/// Appends one JSON object per [`LossRecord`] to a file, flushing after every
+/// line so a run can be plotted while it is still training
+#[derive(Debug)]
+pub struct JsonlLogger {
+    writer: BufWriter<File>,
+}
+
+impl JsonlLogger {
+    /// Opens `path` for appending, creating it if it does not exist
+    pub fn create(path: impl AsRef<Path>) -> Result<Self, TrainError> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        Ok(Self {
+            writer: BufWriter::new(file),
+        })
+    }
+
+    pub fn log(&mut self, record: &LossRecord) -> Result<(), TrainError> {
+        serde_json::to_writer(&mut self.writer, record)?;
+        self.writer.write_all(b"\n")?;
+        self.writer.flush()?;
+        Ok(())
+    }
+}
+
+/// Mean cross-entropy of logits `[batch, time, vocab_size]` against targets `[batch, time]`
+pub fn cross_entropy_loss<B: Backend>(
+    logits: Tensor<B, 3>,
+    targets: Tensor<B, 2, Int>,
+) -> Tensor<B, 1> {
+    let [batch, time, vocab_size] = logits.dims();
+    let device = logits.device();
+    CrossEntropyLossConfig::new().init(&device).forward(
+        logits.reshape([batch * time, vocab_size]),
+        targets.reshape([batch * time]),
+    )
+}
+
+/// Average loss over `config.eval_iters` random batches of `data`
+pub fn estimate_loss<B: Backend, M: LanguageModel<B>>(
+    model: &M,
+    data: &Tensor<B, 1, Int>,
+    config: &TrainingConfig,
+    rng: &mut impl Rng,
+) -> Result<f64, DataError> {
+    let mut total = 0.0;
+    for _ in 0..config.eval_iters {
+        let (x, y) = get_batch(data, config.block_size, config.batch_size, rng)?;
+        total += cross_entropy_loss(model.forward(x), y)
+            .into_scalar()
+            .elem::<f64>();
+    }
+    Ok(total / config.eval_iters as f64)
+}
+
+/// Trains `model` for `config.max_iters` steps on random batches of `train_data`.
+///
+/// Every `eval_interval` steps, and on the last step, the train and validation
+/// losses are estimated in evaluation mode and written to `logger` if given.
+/// Fails with [`TrainError::ZeroEvalInterval`] before training if
+/// `config.eval_interval` is 0.
+#[allow(clippy::too_many_arguments)]
+pub fn train<B, M, O, S>(
+    mut model: M,
+    optimizer: &mut O,
+    scheduler: &mut S,
+    train_data: &Tensor<B, 1, Int>,
+    val_data: &Tensor<B, 1, Int>,
+    config: &TrainingConfig,
+    rng: &mut impl Rng,
+    mut logger: Option<&mut JsonlLogger>,
+) -> Result<M, TrainError>
+where
+    B: AutodiffBackend,
+    M: AutodiffModule<B> + LanguageModel<B>,
+    M::InnerModule: LanguageModel<B::InnerBackend>,
+    O: Optimizer<M, B>,
+    S: LrScheduler,
+{
+    if config.eval_interval == 0 {
+        return Err(TrainError::ZeroEvalInterval);
+    }
+    for step in 0..config.max_iters {
+        let lr = scheduler.step();
+
+        if step % config.eval_interval == 0 || step + 1 == config.max_iters {
+            let eval_model = model.valid();
+            let record = LossRecord {
+                step,
+                train_loss: estimate_loss(&eval_model, &train_data.clone().inner(), config, rng)?,
+                val_loss: estimate_loss(&eval_model, &val_data.clone().inner(), config, rng)?,
+                lr,
+            };
+            if let Some(logger) = logger.as_deref_mut() {
+                logger.log(&record)?;
+            }
+        }
+
+        let (x, y) = get_batch(train_data, config.block_size, config.batch_size, rng)?;
+        let loss = cross_entropy_loss(model.forward(x), y);
+        let grads = GradientsParams::from_grads(loss.backward(), &model);
+        model = optimizer.step(lr, model, grads);
+    }
+
+    Ok(model)
+}
@@ -0,0 +1,277 @@
+use burn::module::{Ignored, Param};
+use burn::nn::{Dropout, DropoutConfig, LayerNorm, LayerNormConfig, Linear, LinearConfig};
+use burn::prelude::*;
+use burn::tensor::activation::{gelu, relu, silu, softmax};
+
+/// Adds the output of `f` back onto its input: `x + f(x)`
+pub fn residual<B: Backend, F: Fn(Tensor<B, 3>) -> Tensor<B, 3>>(
+    x: Tensor<B, 3>,
+    f: F,
+) -> Tensor<B, 3> {
+    x.clone() + f(x)
+}
+
+/// One head of causal self-attention
+#[derive(Module, Debug)]
+pub struct Head<B: Backend> {
+    key: Linear<B>,
+    query: Linear<B>,
+    value: Linear<B>,
+    dropout: Dropout,
+    head_size: usize,
+}
+
+impl<B: Backend> Head<B> {
+    fn new(n_embd: usize, head_size: usize, dropout: f64, device: &B::Device) -> Self {
+        let projection = LinearConfig::new(n_embd, head_size).with_bias(false);
+        Self {
+            key: projection.init(device),
+            query: projection.init(device),
+            value: projection.init(device),
+            dropout: DropoutConfig::new(dropout).init(),
+            head_size,
+        }
+    }
+
+    /// Maps `[batch, time, n_embd]` to `[batch, time, head_size]`
+    pub fn forward(&self, x: Tensor<B, 3>) -> Tensor<B, 3> {
+        self.forward_with_weights(x).0
+    }
+
+    /// Like `forward`, also returning the `[batch, time, time]` attention
+    /// weights after the softmax (before dropout)
+    pub fn forward_with_weights(&self, x: Tensor<B, 3>) -> (Tensor<B, 3>, Tensor<B, 3>) {
+        let [batch, time, _] = x.dims();
+        let k = self.key.forward(x.clone());
+        let q = self.query.forward(x.clone());
+
+        // attention scores, scaled to keep the softmax from saturating
+        let wei = q.matmul(k.swap_dims(1, 2)) * (self.head_size as f64).powf(-0.5);
+        // positions may only attend to themselves and the past
+        let mask = Tensor::<B, 2, Bool>::tril_mask([time, time], 0, &wei.device())
+            .unsqueeze::<3>()
+            .expand([batch, time, time]);
+        let weights = softmax(wei.mask_fill(mask, f32::NEG_INFINITY), 2);
+        let wei = self.dropout.forward(weights.clone());
+
+        (wei.matmul(self.value.forward(x)), weights)
+    }
+}
+
+#[derive(Config, Debug)]
+pub struct MultiHeadAttentionConfig {
+    pub n_embd: usize,
+    pub n_head: usize,
+    #[config(default = 0.0)]
+    pub dropout: f64,
+}
+
+impl MultiHeadAttentionConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> MultiHeadAttention<B> {
+        assert_eq!(
+            self.n_embd % self.n_head,
+            0,
+            "n_embd ({}) must be divisible by n_head ({})",
+            self.n_embd,
+            self.n_head
+        );
+        let head_size = self.n_embd / self.n_head;
+        MultiHeadAttention {
+            heads: (0..self.n_head)
+                .map(|_| Head::new(self.n_embd, head_size, self.dropout, device))
+                .collect(),
+            proj: LinearConfig::new(self.n_embd, self.n_embd).init(device),
+            dropout: DropoutConfig::new(self.dropout).init(),
+        }
+    }
+}
+
+/// Several attention heads in parallel, concatenated and projected back to `n_embd`
+#[derive(Module, Debug)]
+pub struct MultiHeadAttention<B: Backend> {
+    heads: Vec<Head<B>>,
+    proj: Linear<B>,
+    dropout: Dropout,
+}
+
+impl<B: Backend> MultiHeadAttention<B> {
+    pub fn forward(&self, x: Tensor<B, 3>) -> Tensor<B, 3> {
+        self.forward_with_weights(x).0
+    }
+
+    /// Like `forward`, also returning each head's `[batch, time, time]`
+    /// attention weights, e.g. for heatmaps
+    pub fn forward_with_weights(&self, x: Tensor<B, 3>) -> (Tensor<B, 3>, Vec<Tensor<B, 3>>) {
+        let (outs, weights): (Vec<_>, Vec<_>) = self
+            .heads
+            .iter()
+            .map(|h| h.forward_with_weights(x.clone()))
+            .unzip();
+        let out = Tensor::cat(outs, 2);
+        (self.dropout.forward(self.proj.forward(out)), weights)
+    }
+}
+
+/// Nonlinearity applied inside the feed-forward network
+#[derive(Config, Debug, PartialEq, Eq)]
+pub enum Activation {
+    /// `max(0, x)`, as in the original curriculum
+    ReLU,
+    /// `x * Φ(x)`, the standard choice in GPT-2 and later models
+    GELU,
+    /// `x * sigmoid(x)`, also known as swish
+    SiLU,
+}
+
+impl Activation {
+    pub fn forward<B: Backend, const D: usize>(&self, x: Tensor<B, D>) -> Tensor<B, D> {
+        match self {
+            Activation::ReLU => relu(x),
+            Activation::GELU => gelu(x),
+            Activation::SiLU => silu(x),
+        }
+    }
+}
+
+#[derive(Config, Debug)]
+pub struct FeedForwardConfig {
+    pub n_embd: usize,
+    #[config(default = 0.0)]
+    pub dropout: f64,
+    #[config(default = "Activation::ReLU")]
+    pub activation: Activation,
+}
+
+impl FeedForwardConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> FeedForward<B> {
+        FeedForward {
+            fc: LinearConfig::new(self.n_embd, 4 * self.n_embd).init(device),
+            proj: LinearConfig::new(4 * self.n_embd, self.n_embd).init(device),
+            dropout: DropoutConfig::new(self.dropout).init(),
+            activation: Ignored(self.activation.clone()),
+        }
+    }
+}
+
+/// Position-wise MLP with a 4x hidden expansion
+#[derive(Module, Debug)]
+pub struct FeedForward<B: Backend> {
+    fc: Linear<B>,
+    proj: Linear<B>,
+    dropout: Dropout,
+    activation: Ignored<Activation>,
+}
+
+impl<B: Backend> FeedForward<B> {
+    pub fn forward(&self, x: Tensor<B, 3>) -> Tensor<B, 3> {
+        let x = self.activation.0.forward(self.fc.forward(x));
+        self.dropout.forward(self.proj.forward(x))
+    }
+}
+
+#[derive(Config, Debug)]
+pub struct RmsNormConfig {
+    pub d_model: usize,
+    #[config(default = 1e-5)]
+    pub epsilon: f64,
+}
+
+impl RmsNormConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> RmsNorm<B> {
+        RmsNorm {
+            gamma: Param::from_tensor(Tensor::ones([self.d_model], device)),
+            epsilon: self.epsilon,
+        }
+    }
+}
+
+/// Scales the last dimension by the reciprocal of its root-mean-square, then
+/// by a learned `gamma`. Unlike LayerNorm there is no mean subtraction or bias.
+#[derive(Module, Debug)]
+pub struct RmsNorm<B: Backend> {
+    pub gamma: Param<Tensor<B, 1>>,
+    epsilon: f64,
+}
+
+impl<B: Backend> RmsNorm<B> {
+    pub fn forward<const D: usize>(&self, x: Tensor<B, D>) -> Tensor<B, D> {
+        let rms = (x.clone().powi_scalar(2).mean_dim(D - 1) + self.epsilon).sqrt();
+        x / rms * self.gamma.val().unsqueeze()
+    }
+}
+
+/// Normalization layer used inside each transformer block
+#[derive(Config, Debug, PartialEq, Eq)]
+pub enum Normalization {
+    LayerNorm,
+    RmsNorm,
+}
+
+impl Normalization {
+    pub(crate) fn init<B: Backend>(&self, d_model: usize, device: &B::Device) -> Norm<B> {
+        match self {
+            Normalization::LayerNorm => Norm::LayerNorm(LayerNormConfig::new(d_model).init(device)),
+            Normalization::RmsNorm => Norm::RmsNorm(RmsNormConfig::new(d_model).init(device)),
+        }
+    }
+}
+
+#[derive(Module, Debug)]
+pub(crate) enum Norm<B: Backend> {
+    LayerNorm(LayerNorm<B>),
+    RmsNorm(RmsNorm<B>),
+}
+
+impl<B: Backend> Norm<B> {
+    pub(crate) fn forward(&self, x: Tensor<B, 3>) -> Tensor<B, 3> {
+        match self {
+            Norm::LayerNorm(norm) => norm.forward(x),
+            Norm::RmsNorm(norm) => norm.forward(x),
+        }
+    }
+}
+
+#[derive(Config, Debug)]
+pub struct BlockConfig {
+    pub n_embd: usize,
+    pub n_head: usize,
+    #[config(default = 0.0)]
+    pub dropout: f64,
+    #[config(default = "Activation::ReLU")]
+    pub activation: Activation,
+    #[config(default = "Normalization::LayerNorm")]
+    pub norm: Normalization,
+}
+
+impl BlockConfig {
+    pub fn init<B: Backend>(&self, device: &B::Device) -> Block<B> {
+        Block {
+            sa: MultiHeadAttentionConfig::new(self.n_embd, self.n_head)
+                .with_dropout(self.dropout)
+                .init(device),
+            ffwd: FeedForwardConfig::new(self.n_embd)
+                .with_dropout(self.dropout)
+                .with_activation(self.activation.clone())
+                .init(device),
+            ln1: self.norm.init(self.n_embd, device),
+            ln2: self.norm.init(self.n_embd, device),
+        }
+    }
+}
+
+/// Transformer block: communication (attention) followed by computation (feed-forward),
+/// each wrapped in a pre-norm residual connection
+#[derive(Module, Debug)]
+pub struct Block<B: Backend> {
+    sa: MultiHeadAttention<B>,
+    ffwd: FeedForward<B>,
+    ln1: Norm<B>,
+    ln2: Norm<B>,
+}
+
+impl<B: Backend> Block<B> {
+    pub fn forward(&self, x: Tensor<B, 3>) -> Tensor<B, 3> {
+        let x = residual(x, |x| self.sa.forward(self.ln1.forward(x)));
+        residual(x, |x| self.ffwd.forward(self.ln2.forward(x)))
+    }
+}
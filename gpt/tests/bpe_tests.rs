@@ -0,0 +1,84 @@
+use std::fs;
+use std::path::PathBuf;
+
+use gpt::bpe::{BpeError, BpeTokenizer, pretokenize};
+
+const TRAINING_TEXT: &str = "First Citizen:\nBefore we proceed any further, hear me speak.\n\n\
+All:\nSpeak, speak.\n\nFirst Citizen:\nYou are all resolved rather to die than to famish?";
+
+fn temp_path(name: &str) -> PathBuf {
+    std::env::temp_dir().join(format!("gpt_bpe_{}_{}.json", name, std::process::id()))
+}
+
+#[test]
+fn test_train_learns_merges() {
+    let tokenizer = BpeTokenizer::train(TRAINING_TEXT, 256 + 20).unwrap();
+
+    assert_eq!(tokenizer.vocab_size(), 276);
+    assert_eq!(tokenizer.merges().len(), 20);
+
+    let encoded = tokenizer.encode("First Citizen: speak");
+    assert!(encoded.len() < "First Citizen: speak".len());
+    assert_eq!(tokenizer.decode(&encoded), "First Citizen: speak");
+}
+
+#[test]
+fn test_save_load_round_trip() {
+    let tokenizer = BpeTokenizer::train(TRAINING_TEXT, 256 + 20).unwrap();
+    let path = temp_path("round_trip");
+
+    tokenizer.save(&path).unwrap();
+    let loaded = BpeTokenizer::load(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+
+    let sample = "Speak, Citizen! We are resolved.";
+    assert_eq!(loaded.merges(), tokenizer.merges());
+    assert_eq!(loaded.encode(sample), tokenizer.encode(sample));
+    assert_eq!(loaded.decode(&loaded.encode(sample)), sample);
+}
+
+#[test]
+fn test_load_rejects_other_version() {
+    let path = temp_path("version");
+    fs::write(&path, r#"{"version": 99, "merges": [], "vocab": []}"#).unwrap();
+
+    let result = BpeTokenizer::load(&path);
+    fs::remove_file(&path).unwrap();
+
+    assert!(matches!(result, Err(BpeError::UnsupportedVersion(99))));
+}
+
+#[test]
+fn test_pretokenize_splits_contractions() {
+    let text = "I'll say it's 42 times!!  Ok";
+    let chunks = pretokenize(text);
+    assert_eq!(
+        chunks,
+        vec![
+            "I", "'ll", " say", " it", "'s", " 42", " times", "!!", " ", " Ok"
+        ]
+    );
+    assert_eq!(chunks.concat(), text);
+}
+
+#[test]
+fn test_merges_stay_within_pretokenized_chunks() {
+    // chunks are "a", " b", " a", " b": "a " and "b " never appear in one
+    let tokenizer = BpeTokenizer::train("a b a b", 256 + 10).unwrap();
+
+    let learned: Vec<String> = (256..tokenizer.vocab_size())
+        .map(|id| tokenizer.decode(&[id]))
+        .collect();
+    assert_eq!(learned, vec![" b", " a"]);
+    for token in &learned {
+        assert_eq!(
+            pretokenize(token).len(),
+            1,
+            "merge {token:?} crosses a chunk"
+        );
+    }
+
+    let encoded = tokenizer.encode("a b a b");
+    assert_eq!(encoded, vec![usize::from(b'a'), 256, 257, 256]);
+    assert_eq!(tokenizer.decode(&encoded), "a b a b");
+}
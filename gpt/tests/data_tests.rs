@@ -0,0 +1,137 @@
+use burn::backend::NdArray;
+use burn::tensor::{Int, Tensor};
+use gpt::data::{DataError, TextDataset, get_batch, load_corpus_streaming};
+use gpt::tokenizer::Tokenizer;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+type TestBackend = NdArray;
+
+#[test]
+fn test_get_batch_shapes_and_targets() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..20, &device);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let (x, y) = get_batch(&data, 8, 4, &mut rng).unwrap();
+    assert_eq!(x.dims(), [4, 8]);
+    assert_eq!(y.dims(), [4, 8]);
+
+    // data is 0..20, so every target is its input plus one
+    (x + 1).into_data().assert_eq(&y.into_data(), true);
+}
+
+#[test]
+fn test_get_batch_exact_length() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..9, &device);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let (x, y) = get_batch(&data, 8, 2, &mut rng).unwrap();
+    assert_eq!(x.dims(), [2, 8]);
+    assert_eq!(y.dims(), [2, 8]);
+}
+
+#[test]
+fn test_get_batch_too_short() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..8, &device);
+    let mut rng = StdRng::seed_from_u64(0);
+
+    let result = get_batch(&data, 8, 4, &mut rng);
+    assert!(matches!(
+        result,
+        Err(DataError::SequenceTooShort {
+            len: 8,
+            block_size: 8
+        })
+    ));
+}
+
+#[test]
+fn test_text_dataset_len_and_get() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..10, &device);
+    let dataset = TextDataset::new(data, 4).unwrap();
+
+    assert_eq!(dataset.len(), 6);
+
+    let (x, y) = dataset.get(0);
+    x.into_data().assert_eq(
+        &Tensor::<TestBackend, 1, Int>::from_data([0, 1, 2, 3], &device).into_data(),
+        true,
+    );
+    y.into_data().assert_eq(
+        &Tensor::<TestBackend, 1, Int>::from_data([1, 2, 3, 4], &device).into_data(),
+        true,
+    );
+
+    let (x, y) = dataset.get(5);
+    x.into_data().assert_eq(
+        &Tensor::<TestBackend, 1, Int>::from_data([5, 6, 7, 8], &device).into_data(),
+        true,
+    );
+    y.into_data().assert_eq(
+        &Tensor::<TestBackend, 1, Int>::from_data([6, 7, 8, 9], &device).into_data(),
+        true,
+    );
+}
+
+#[test]
+fn test_text_dataset_iter_batches() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..10, &device);
+    let dataset = TextDataset::new(data, 4).unwrap();
+
+    let shapes: Vec<_> = dataset
+        .iter_batches(4)
+        .map(|(x, y)| (x.dims(), y.dims()))
+        .collect();
+    assert_eq!(shapes, vec![([4, 4], [4, 4]), ([2, 4], [2, 4])]);
+}
+
+#[test]
+fn test_text_dataset_too_short() {
+    let device = Default::default();
+    let data = Tensor::<TestBackend, 1, Int>::arange(0..4, &device);
+    assert!(matches!(
+        TextDataset::new(data, 4),
+        Err(DataError::SequenceTooShort {
+            len: 4,
+            block_size: 4
+        })
+    ));
+}
+
+#[test]
+fn test_load_corpus_streaming_matches_encode() {
+    let device = Default::default();
+    let text = include_str!("../input.txt");
+    let tokenizer = Tokenizer::new(text);
+
+    let path = concat!(env!("CARGO_MANIFEST_DIR"), "/input.txt");
+    let streamed = load_corpus_streaming::<TestBackend>(path, &tokenizer, &device).unwrap();
+    let expected: Vec<i64> = tokenizer.encode(text).iter().map(|&t| t as i64).collect();
+    assert_eq!(streamed.into_data().iter::<i64>().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_load_corpus_streaming_multibyte_chunk_boundaries() {
+    let device = Default::default();
+    // 2- and 3-byte characters spread over several read chunks
+    let text = "a\u{e9}\u{2603}".repeat(30_000);
+    let tokenizer = Tokenizer::new(&text);
+    let path = std::env::temp_dir().join(format!("gpt_corpus_{}.txt", std::process::id()));
+    std::fs::write(&path, &text).unwrap();
+
+    let streamed = load_corpus_streaming::<TestBackend>(&path, &tokenizer, &device);
+    std::fs::remove_file(&path).unwrap();
+    let expected: Vec<i64> = tokenizer.encode(&text).iter().map(|&t| t as i64).collect();
+    assert_eq!(
+        streamed.unwrap().into_data().iter::<i64>().collect::<Vec<_>>(),
+        expected
+    );
+
+    let missing = load_corpus_streaming::<TestBackend>("no/such/file.txt", &tokenizer, &device);
+    assert!(matches!(missing, Err(DataError::Io(_))));
+}
@@ -0,0 +1,46 @@
+use burn::backend::NdArray;
+use burn::tensor::Tensor;
+use gpt::interop::{mlp_forward_tensor, tensor_to_values, values_to_tensor};
+use micrograd::engine::Value;
+use micrograd::nn::MLP;
+
+type TestBackend = NdArray;
+
+#[test]
+fn test_tensor_values_roundtrip() {
+    let device = Default::default();
+    let x = Tensor::<TestBackend, 2>::from_floats([[1.0, 2.0, 3.0], [-1.0, 0.5, 0.0]], &device);
+
+    let values = tensor_to_values(x.clone());
+    assert_eq!(values.len(), 2);
+    assert!(values.iter().all(|row| row.len() == 3));
+    assert_eq!(values[1][1].data(), 0.5);
+
+    let back = values_to_tensor::<TestBackend>(&values, &device);
+    back.into_data().assert_eq(&x.into_data(), true);
+}
+
+#[test]
+fn test_mlp_forward_through_tensors() {
+    let device = Default::default();
+    let mlp = MLP::new(3, vec![4, 2]);
+    let x = Tensor::<TestBackend, 2>::from_floats([[1.0, 2.0, 3.0], [-1.0, 0.5, 0.0]], &device);
+
+    let out = mlp_forward_tensor(&mlp, x);
+    assert_eq!(out.dims(), [2, 2]);
+
+    let expected: Vec<f32> = [[1.0, 2.0, 3.0], [-1.0, 0.5, 0.0]]
+        .iter()
+        .flat_map(|row| {
+            let row: Vec<Value> = row.iter().map(|&v| Value::new(v)).collect();
+            mlp.forward(&row)
+                .iter()
+                .map(|v| v.data() as f32)
+                .collect::<Vec<_>>()
+        })
+        .collect();
+    let actual: Vec<f32> = out.into_data().to_vec().unwrap();
+    for (a, e) in actual.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-6);
+    }
+}
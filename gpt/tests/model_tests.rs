@@ -0,0 +1,295 @@
+use burn::backend::{Autodiff, NdArray};
+use burn::module::Module;
+use burn::optim::{GradientsParams, Optimizer, SgdConfig};
+use burn::tensor::{Int, Tensor, TensorData};
+use gpt::model::{
+    BigramLanguageModel, CountBigramModel, GptConfig, LanguageModel, PositionalEncoding,
+    sinusoidal_encoding,
+};
+use gpt::sampling::GenerationConfig;
+use gpt::tokenizer::Tokenizer;
+use gpt::transformer::BlockConfig;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+
+type TestBackend = NdArray;
+
+#[test]
+fn test_bigram_forward_shape() {
+    let device = Default::default();
+    let model = BigramLanguageModel::<TestBackend>::new(10, &device);
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[1, 2, 3], [4, 5, 6]], &device);
+
+    assert_eq!(model.forward(idx).dims(), [2, 3, 10]);
+}
+
+#[test]
+fn test_generate_appends_tokens() {
+    let device = Default::default();
+    let model = BigramLanguageModel::<TestBackend>::new(10, &device);
+    let idx = Tensor::<TestBackend, 2, Int>::zeros([2, 1], &device);
+    let config = GenerationConfig {
+        top_p: Some(0.9),
+        ..Default::default()
+    };
+
+    let out = model.generate(idx, 5, &config, &mut rand::rng());
+    assert_eq!(out.dims(), [2, 6]);
+    let tokens: Vec<i64> = out.into_data().iter::<i64>().collect();
+    assert!(tokens.iter().all(|&t| (0..10).contains(&t)));
+}
+
+#[test]
+fn test_generate_same_seed_same_tokens() {
+    let device = Default::default();
+    let model = BigramLanguageModel::<TestBackend>::new(10, &device);
+    let idx = Tensor::<TestBackend, 2, Int>::zeros([2, 1], &device);
+    let config = GenerationConfig::default();
+
+    let generate = |seed| {
+        model
+            .generate(idx.clone(), 20, &config, &mut StdRng::seed_from_u64(seed))
+            .into_data()
+            .iter::<i64>()
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(generate(7), generate(7));
+    assert_ne!(generate(7), generate(8));
+}
+
+#[test]
+fn test_generate_greedy_ignores_rng() {
+    let device = Default::default();
+    let model = BigramLanguageModel::<TestBackend>::new(10, &device);
+    let idx = Tensor::<TestBackend, 2, Int>::zeros([1, 1], &device);
+    let config = GenerationConfig {
+        temperature: 0.0,
+        ..Default::default()
+    };
+
+    let mut rng = StdRng::seed_from_u64(0);
+    let a = model.generate(idx.clone(), 10, &config, &mut rng);
+    let b = model.generate(idx, 10, &config, &mut StdRng::seed_from_u64(1));
+    assert_eq!(a.into_data(), b.into_data());
+    // the rng was left untouched
+    assert_eq!(rng.random::<u64>(), StdRng::seed_from_u64(0).random::<u64>());
+}
+
+#[test]
+fn test_generate_from_prompt() {
+    let device = Default::default();
+    let tokenizer = Tokenizer::new("hello world");
+    let model = BigramLanguageModel::<TestBackend>::new(tokenizer.vocab_size(), &device);
+    let config = GenerationConfig::default();
+
+    let text = model
+        .generate_from_prompt("w", &tokenizer, 10, &config, &device, &mut rand::rng())
+        .unwrap();
+    assert!(text.starts_with('w'));
+    assert_eq!(text.chars().count(), 11);
+}
+
+#[test]
+fn test_generate_from_empty_prompt() {
+    let device = Default::default();
+    let tokenizer = Tokenizer::new("hello world");
+    let model = BigramLanguageModel::<TestBackend>::new(tokenizer.vocab_size(), &device);
+    let config = GenerationConfig::default();
+
+    let text = model
+        .generate_from_prompt("", &tokenizer, 5, &config, &device, &mut rand::rng())
+        .unwrap();
+    assert_eq!(text.chars().count(), 6);
+    assert_eq!(text.chars().next(), Some(tokenizer.decode_char(0).unwrap()));
+}
+
+#[test]
+fn test_generate_from_prompt_out_of_vocab() {
+    let device = Default::default();
+    let tokenizer = Tokenizer::new("abc");
+    let model = BigramLanguageModel::<TestBackend>::new(tokenizer.vocab_size(), &device);
+
+    let result = model.generate_from_prompt(
+        "xyz",
+        &tokenizer,
+        5,
+        &GenerationConfig::default(),
+        &device,
+        &mut rand::rng(),
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_sinusoidal_encoding_formula() {
+    let device = Default::default();
+    let n_embd = 8;
+    let table: Vec<f32> = sinusoidal_encoding::<TestBackend>(4, n_embd, &device)
+        .into_data()
+        .to_vec()
+        .unwrap();
+    let at = |pos: usize, dim: usize| table[pos * n_embd + dim] as f64;
+
+    // position 0 is sin(0) = 0 in even columns and cos(0) = 1 in odd ones
+    for dim in 0..n_embd {
+        assert!((at(0, dim) - (dim % 2) as f64).abs() < 1e-6);
+    }
+    assert!((at(1, 0) - 1.0_f64.sin()).abs() < 1e-6);
+    assert!((at(1, 1) - 1.0_f64.cos()).abs() < 1e-6);
+    let angle = 3.0 / 10000_f64.powf(4.0 / 8.0);
+    assert!((at(3, 4) - angle.sin()).abs() < 1e-6);
+    assert!((at(3, 5) - angle.cos()).abs() < 1e-6);
+}
+
+#[test]
+fn test_gpt_forward_shape_for_each_positional_encoding() {
+    let device = Default::default();
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[1, 2, 3], [4, 5, 6]], &device);
+
+    for encoding in [PositionalEncoding::Learned, PositionalEncoding::Sinusoidal] {
+        let model = GptConfig::new(10)
+            .with_block_size(8)
+            .with_n_embd(16)
+            .with_n_head(2)
+            .with_positional_encoding(encoding)
+            .init::<TestBackend>(&device);
+
+        assert_eq!(model.forward(idx.clone()).dims(), [2, 3, 10]);
+        assert_eq!(model.block_size(), 8);
+    }
+}
+
+/// Puts all probability mass on a single token, whatever the context
+struct RiggedModel {
+    token: usize,
+    vocab_size: usize,
+}
+
+impl LanguageModel<TestBackend> for RiggedModel {
+    fn forward(&self, idx: Tensor<TestBackend, 2, Int>) -> Tensor<TestBackend, 3> {
+        let [batch, time] = idx.dims();
+        let mut logits = vec![0.0_f32; self.vocab_size];
+        logits[self.token] = 100.0;
+        Tensor::<TestBackend, 1>::from_data(
+            TensorData::new(logits, [self.vocab_size]),
+            &idx.device(),
+        )
+        .reshape([1, 1, self.vocab_size])
+        .expand([batch, time, self.vocab_size])
+    }
+}
+
+#[test]
+fn test_generate_stops_at_eos() {
+    let device = Default::default();
+    let model = RiggedModel {
+        token: 3,
+        vocab_size: 5,
+    };
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[1, 2]], &device);
+    let config = GenerationConfig {
+        eos_id: Some(3),
+        ..Default::default()
+    };
+
+    let out = model.generate(idx.clone(), 10, &config, &mut rand::rng());
+    let tokens: Vec<i64> = out.into_data().iter::<i64>().collect();
+    assert_eq!(tokens, vec![1, 2, 3]);
+
+    // without an eos token the full budget is used
+    let out = model.generate(idx, 10, &GenerationConfig::default(), &mut rand::rng());
+    assert_eq!(out.dims(), [1, 12]);
+}
+
+fn tiny_gpt() -> GptConfig {
+    GptConfig::new(10)
+        .with_block_size(8)
+        .with_n_embd(16)
+        .with_n_head(2)
+        .with_dropout(0.0)
+}
+
+#[test]
+fn test_tied_weights_reduce_parameters() {
+    let device = Default::default();
+    let untied = tiny_gpt().init::<TestBackend>(&device);
+    let tied = tiny_gpt().with_tie_weights(true).init::<TestBackend>(&device);
+
+    // the untied lm_head owns a [16, 10] weight and a bias of 10
+    assert_eq!(untied.num_params() - tied.num_params(), 16 * 10 + 10);
+
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[1, 2, 3]], &device);
+    assert_eq!(tied.forward(idx).dims(), [1, 3, 10]);
+}
+
+#[test]
+fn test_tied_weights_follow_embedding_updates() {
+    type AutodiffBackend = Autodiff<TestBackend>;
+    let device = Default::default();
+    let model = tiny_gpt().with_tie_weights(true).init::<AutodiffBackend>(&device);
+    let before = model.lm_head_weight().into_data();
+
+    let idx = Tensor::<AutodiffBackend, 2, Int>::from_data([[1, 2, 3]], &device);
+    let loss = model.forward(idx).powi_scalar(2).mean();
+    let grads = GradientsParams::from_grads(loss.backward(), &model);
+    let model = SgdConfig::new().init().step(0.1, model, grads);
+
+    let after = model.lm_head_weight();
+    assert_ne!(before, after.clone().into_data());
+    after
+        .into_data()
+        .assert_eq(&model.token_embedding_weight().transpose().into_data(), true);
+}
+
+#[test]
+fn test_count_bigram_model() {
+    let device = Default::default();
+    let text = "abcabca";
+    let tokenizer = Tokenizer::new(text);
+    let tokens = tokenizer.encode(text);
+    let model = CountBigramModel::new(&tokens, tokenizer.vocab_size(), 0.0);
+
+    // a row sums to the number of times its token is followed by another
+    let row_sums: Vec<usize> = model.counts().iter().map(|r| r.iter().sum()).collect();
+    assert_eq!(row_sums, vec![2, 2, 2]);
+    assert_eq!(model.counts()[0], vec![0, 2, 0]);
+    for row in model.probs() {
+        assert!((row.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+    }
+
+    // smoothing keeps unseen pairs possible
+    let smoothed = CountBigramModel::new(&tokens, tokenizer.vocab_size(), 1.0);
+    assert_eq!(smoothed.probs()[0], vec![0.2, 0.6, 0.2]);
+
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[0]], &device);
+    let mut rng = StdRng::seed_from_u64(0);
+    let out = smoothed.generate(idx.clone(), 20, &GenerationConfig::default(), &mut rng);
+    let generated: Vec<i64> = out.into_data().iter::<i64>().collect();
+    assert_eq!(generated.len(), 21);
+    assert!(generated.iter().all(|&t| (0..3).contains(&t)));
+
+    // without smoothing the corpus is deterministic: a -> b -> c -> a
+    let out = model.generate(idx, 5, &GenerationConfig::default(), &mut rng);
+    let generated: Vec<usize> = out.into_data().iter::<i64>().map(|t| t as usize).collect();
+    assert_eq!(tokenizer.decode(&generated), "abcabc");
+}
+
+#[test]
+fn test_gpt_stacks_n_layer_blocks() {
+    let device = Default::default();
+    let params = |n_layer| {
+        tiny_gpt()
+            .with_n_layer(n_layer)
+            .init::<TestBackend>(&device)
+            .num_params()
+    };
+    let block = BlockConfig::new(16, 2).init::<TestBackend>(&device);
+
+    // every extra layer adds exactly one block's parameters
+    assert_eq!(params(2) - params(1), block.num_params());
+    assert_eq!(params(4) - params(2), 2 * block.num_params());
+
+    let model = tiny_gpt().with_n_layer(2).init::<TestBackend>(&device);
+    let idx = Tensor::<TestBackend, 2, Int>::from_data([[1, 2, 3], [4, 5, 6]], &device);
+    assert_eq!(model.forward(idx).dims(), [2, 3, 10]);
+}
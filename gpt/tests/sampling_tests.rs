@@ -0,0 +1,78 @@
+use gpt::sampling::{
+    GenerationConfig, apply_repetition_penalty, sample_next, softmax, top_p_filter,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn test_top_p_collapses_peaked_distribution() {
+    let mut rng = StdRng::seed_from_u64(42);
+    let logits = [1.0, 8.0, 2.0, 0.5];
+    let config = GenerationConfig {
+        top_p: Some(0.5),
+        ..Default::default()
+    };
+
+    for _ in 0..100 {
+        assert_eq!(sample_next(&logits, &config, &mut rng), 1);
+    }
+}
+
+#[test]
+fn test_top_p_filter_keeps_nucleus() {
+    let mut probs = vec![0.1, 0.5, 0.3, 0.1];
+    top_p_filter(&mut probs, 0.7);
+
+    assert_eq!(probs[0], 0.0);
+    assert_eq!(probs[3], 0.0);
+    assert!((probs[1] - 0.625).abs() < 1e-12);
+    assert!((probs[2] - 0.375).abs() < 1e-12);
+}
+
+#[test]
+fn test_top_p_near_one_keeps_full_distribution() {
+    let original = softmax(&[0.3, -1.0, 2.0, 0.0], 1.0);
+    let mut probs = original.clone();
+    top_p_filter(&mut probs, 0.999);
+
+    for (p, q) in probs.iter().zip(original.iter()) {
+        assert!((p - q).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_top_p_composes_with_temperature() {
+    let mut rng = StdRng::seed_from_u64(7);
+    let logits = [2.0, 1.9, -5.0];
+    // a low temperature sharpens the distribution so the nucleus holds a single token
+    let config = GenerationConfig {
+        temperature: 0.01,
+        top_p: Some(0.9),
+        ..Default::default()
+    };
+
+    for _ in 0..50 {
+        assert_eq!(sample_next(&logits, &config, &mut rng), 0);
+    }
+}
+
+#[test]
+fn test_repetition_penalty_lowers_repeated_tokens() {
+    let logits = [2.0, -1.0, 0.5, 1.0];
+    let previous = [0, 1, 0];
+
+    let mut unchanged = logits;
+    apply_repetition_penalty(&mut unchanged, &previous, 1.0);
+    assert_eq!(unchanged, logits);
+
+    let mut penalized = logits;
+    apply_repetition_penalty(&mut penalized, &previous, 5.0);
+    // positive logits are divided, negative ones multiplied, each only once
+    assert_eq!(penalized, [0.4, -5.0, 0.5, 1.0]);
+
+    let before = softmax(&logits, 1.0);
+    let after = softmax(&penalized, 1.0);
+    assert!(after[0] < before[0]);
+    assert!(after[1] < before[1]);
+    assert!(after[3] > before[3]);
+}
@@ -0,0 +1,24 @@
+use burn::lr_scheduler::LrScheduler;
+use gpt::schedule::WarmupLinear;
+
+#[test]
+fn test_warmup_linear_key_points() {
+    let schedule = WarmupLinear::new(10, 100, 3e-4);
+
+    assert_eq!(schedule.lr_at(0), 0.0);
+    assert!((schedule.lr_at(5) - 1.5e-4).abs() < 1e-12);
+    assert!((schedule.lr_at(10) - 3e-4).abs() < 1e-12);
+    assert!((schedule.lr_at(55) - 1.5e-4).abs() < 1e-12);
+    assert_eq!(schedule.lr_at(100), 0.0);
+    assert_eq!(schedule.lr_at(150), 0.0);
+}
+
+#[test]
+fn test_warmup_linear_step_follows_lr_at() {
+    let reference = WarmupLinear::new(4, 12, 1.0);
+    let mut schedule = reference;
+
+    for step in 0..16 {
+        assert_eq!(schedule.step(), reference.lr_at(step));
+    }
+}
@@ -0,0 +1,165 @@
+use gpt::tokenizer::{Tokenizer, TokenizerError, augment_drop, augment_swap, check_roundtrip};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+#[test]
+fn test_decode_char() {
+    let tokenizer = Tokenizer::new("hello world");
+
+    for (idx, c) in tokenizer.get_vocab().into_iter().enumerate() {
+        assert_eq!(tokenizer.decode_char(idx).unwrap(), c);
+    }
+}
+
+#[test]
+fn test_decode_char_out_of_bounds() {
+    let tokenizer = Tokenizer::new("abc");
+
+    assert!(matches!(
+        tokenizer.decode_char(3),
+        Err(TokenizerError::IndexOutOfBounds(3))
+    ));
+}
+
+#[test]
+fn test_decode_lossy() {
+    let tokenizer = Tokenizer::new("abc");
+
+    assert_eq!(tokenizer.decode_lossy(&[0, 7, 1, 99, 2]), "a\u{FFFD}b\u{FFFD}c");
+    assert_eq!(tokenizer.decode_lossy(&[2, 1, 0]), tokenizer.decode(&[2, 1, 0]));
+}
+
+#[test]
+fn test_encode_batch() {
+    let tokenizer = Tokenizer::new("abc");
+
+    assert_eq!(
+        tokenizer.encode_batch(&["ab", "", "cab"]).unwrap(),
+        vec![vec![0, 1], vec![], vec![2, 0, 1]]
+    );
+    assert!(matches!(
+        tokenizer.encode_batch(&["ab", "xa"]),
+        Err(TokenizerError::CharacterNotInVocabulary('x'))
+    ));
+}
+
+#[test]
+fn test_encode_batch_padded() {
+    let tokenizer = Tokenizer::new("abc");
+
+    let batch = tokenizer.encode_batch_padded(&["a", "abc", "ba"], 9);
+    assert!(batch.iter().all(|seq| seq.len() == 3));
+    assert_eq!(batch, vec![vec![0, 9, 9], vec![0, 1, 2], vec![1, 0, 9]]);
+}
+
+#[test]
+fn test_encode_batch_with_mask() {
+    let tokenizer = Tokenizer::new("abc");
+
+    // pad with a real token id to check the mask does not rely on the value
+    let (batch, mask) = tokenizer.encode_batch_with_mask(&["a", "abc", "", "ba"], 0);
+    assert_eq!(batch, vec![vec![0, 0, 0], vec![0, 1, 2], vec![0, 0, 0], vec![1, 0, 0]]);
+    assert_eq!(
+        mask,
+        vec![
+            vec![true, false, false],
+            vec![true, true, true],
+            vec![false, false, false],
+            vec![true, true, false],
+        ]
+    );
+}
+
+#[test]
+fn test_check_roundtrip_shakespeare() {
+    let text = include_str!("../input.txt");
+    let tokenizer = Tokenizer::new(text);
+
+    assert!(check_roundtrip(&tokenizer, "First Citizen:\nBefore we proceed any further, hear me speak."));
+    assert!(check_roundtrip(&tokenizer, ""));
+    assert!(!check_roundtrip(&tokenizer, "caf\u{e9} \u{2603}"));
+}
+
+#[test]
+fn test_index_of_and_char_of() {
+    let tokenizer = Tokenizer::new("hello world");
+
+    for c in "helo wrd".chars() {
+        let idx = tokenizer.index_of(c).unwrap();
+        assert_eq!(vec![idx], tokenizer.encode(&c.to_string()));
+        assert_eq!(tokenizer.char_of(idx), Some(c));
+        assert_eq!(tokenizer.decode(&[idx]), c.to_string());
+    }
+
+    assert_eq!(tokenizer.index_of('z'), None);
+    assert_eq!(tokenizer.char_of(tokenizer.vocab_size()), None);
+}
+
+#[test]
+fn test_extend_keeps_existing_ids() {
+    let mut tokenizer = Tokenizer::new("cab");
+    let before = tokenizer.encode("abc");
+
+    tokenizer.extend(&['z', 'a', 'x', 'z']);
+
+    assert_eq!(tokenizer.encode("abc"), before);
+    assert_eq!(tokenizer.vocab_size(), 5);
+    assert_eq!(tokenizer.index_of('z'), Some(3));
+    assert_eq!(tokenizer.index_of('x'), Some(4));
+    assert_eq!(tokenizer.decode(&[4, 0, 3]), "xaz");
+}
+
+#[test]
+fn test_new_by_frequency() {
+    let text = "abracadabra";
+    let tokenizer = Tokenizer::new_by_frequency(text);
+
+    // a: 5, b: 2, r: 2, c: 1, d: 1, with ties in code-point order
+    assert_eq!(tokenizer.index_of('a'), Some(0));
+    assert_eq!(tokenizer.decode(&[0, 1, 2, 3, 4]), "abrcd");
+    assert!(check_roundtrip(&tokenizer, text));
+    assert_eq!(tokenizer.vocab_size(), Tokenizer::new(text).vocab_size());
+
+    let shakespeare = Tokenizer::new_by_frequency(include_str!("../input.txt"));
+    assert_eq!(shakespeare.char_of(0), Some(' '));
+}
+
+#[test]
+fn test_augment_swap_and_drop() {
+    let ids: Vec<usize> = (0..10).collect();
+    let mut rng = StdRng::seed_from_u64(42);
+
+    assert_eq!(augment_swap(&ids, 0.0, &mut rng), ids);
+    assert_eq!(augment_drop(&ids, 0.0, &mut rng), ids);
+
+    let swapped = augment_swap(&ids, 1.0, &mut rng);
+    assert_eq!(swapped, vec![1, 0, 3, 2, 5, 4, 7, 6, 9, 8]);
+    assert!(swapped.iter().zip(&ids).all(|(a, b)| a != b));
+    assert!(augment_drop(&ids, 1.0, &mut rng).is_empty());
+
+    let dropped = augment_drop(&ids, 0.5, &mut rng);
+    assert!(dropped.len() <= ids.len());
+    assert!(dropped.windows(2).all(|w| w[0] < w[1]));
+}
+
+#[test]
+fn test_from_vocab_keeps_given_order() {
+    let tokenizer = Tokenizer::from_vocab(&['z', '\n', 'a', ' ', 'M']).unwrap();
+
+    assert_eq!(tokenizer.vocab_size(), 5);
+    assert_eq!(tokenizer.encode("za M\n"), vec![0, 2, 3, 4, 1]);
+    assert_eq!(tokenizer.char_of(0), Some('z'));
+    assert_eq!(tokenizer.decode(&[4, 2, 0]), "Maz");
+    assert!(matches!(
+        tokenizer.try_encode("b"),
+        Err(TokenizerError::CharacterNotInVocabulary('b'))
+    ));
+}
+
+#[test]
+fn test_from_vocab_rejects_duplicates() {
+    assert!(matches!(
+        Tokenizer::from_vocab(&['a', 'b', 'a']),
+        Err(TokenizerError::DuplicateCharacter('a'))
+    ));
+}
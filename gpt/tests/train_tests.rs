@@ -0,0 +1,88 @@
+use std::fs;
+
+use burn::backend::{Autodiff, NdArray};
+use burn::optim::SgdConfig;
+use burn::tensor::{Int, Tensor};
+use gpt::model::BigramLanguageModel;
+use gpt::schedule::WarmupLinear;
+use gpt::train::{JsonlLogger, LossRecord, TrainError, TrainingConfig, train};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+
+type TestBackend = Autodiff<NdArray>;
+
+#[test]
+fn test_train_streams_jsonl_loss_log() {
+    let device = Default::default();
+    let tokens: Vec<i64> = (0..64).map(|i| i % 5).collect();
+    let data = Tensor::<TestBackend, 1, Int>::from_data(tokens.as_slice(), &device);
+    let model = BigramLanguageModel::<TestBackend>::new(5, &device);
+    let config = TrainingConfig::new()
+        .with_max_iters(5)
+        .with_eval_interval(2)
+        .with_eval_iters(2)
+        .with_batch_size(4)
+        .with_block_size(8);
+
+    let path = std::env::temp_dir().join(format!("gpt_train_log_{}.jsonl", std::process::id()));
+    let _ = fs::remove_file(&path);
+    let mut logger = JsonlLogger::create(&path).unwrap();
+    let mut rng = StdRng::seed_from_u64(0);
+    train(
+        model,
+        &mut SgdConfig::new().init(),
+        &mut WarmupLinear::new(2, 5, 0.1),
+        &data,
+        &data,
+        &config,
+        &mut rng,
+        Some(&mut logger),
+    )
+    .unwrap();
+
+    let contents = fs::read_to_string(&path).unwrap();
+    fs::remove_file(&path).unwrap();
+    let records: Vec<LossRecord> = contents
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    // steps 0, 2 and the final step 4
+    assert_eq!(records.len(), 3);
+    assert_eq!(
+        records.iter().map(|r| r.step).collect::<Vec<_>>(),
+        vec![0, 2, 4]
+    );
+    assert_eq!(records[0].lr, 0.0);
+    assert!((records[1].lr - 0.1).abs() < 1e-12);
+    assert!(
+        records
+            .iter()
+            .all(|r| r.train_loss.is_finite() && r.val_loss.is_finite())
+    );
+}
+
+#[test]
+fn test_train_rejects_zero_eval_interval() {
+    let device = Default::default();
+    let tokens: Vec<i64> = (0..64).map(|i| i % 5).collect();
+    let data = Tensor::<TestBackend, 1, Int>::from_data(tokens.as_slice(), &device);
+    let model = BigramLanguageModel::<TestBackend>::new(5, &device);
+    let config = TrainingConfig::new()
+        .with_max_iters(3)
+        .with_eval_interval(0)
+        .with_batch_size(4)
+        .with_block_size(8);
+
+    let result = train(
+        model,
+        &mut SgdConfig::new().init(),
+        &mut WarmupLinear::new(1, 3, 0.1),
+        &data,
+        &data,
+        &config,
+        &mut StdRng::seed_from_u64(0),
+        None,
+    );
+    assert!(matches!(result, Err(TrainError::ZeroEvalInterval)));
+}
@@ -0,0 +1,126 @@
+use burn::backend::NdArray;
+use burn::module::Param;
+use burn::tensor::{Distribution, Tensor};
+use gpt::transformer::{
+    Activation, BlockConfig, FeedForwardConfig, MultiHeadAttentionConfig, Normalization,
+    RmsNormConfig, residual,
+};
+
+type TestBackend = NdArray;
+
+#[test]
+fn test_residual_with_zero_sublayer_is_identity() {
+    let device = Default::default();
+    let x = Tensor::<TestBackend, 3>::random([2, 4, 8], Distribution::Default, &device);
+
+    let out = residual(x.clone(), |x| x.zeros_like());
+    out.into_data().assert_eq(&x.into_data(), true);
+}
+
+#[test]
+fn test_residual_adds_sublayer_output() {
+    let device = Default::default();
+    let x = Tensor::<TestBackend, 3>::ones([1, 2, 3], &device);
+
+    let out = residual(x, |x| x * 2.0);
+    out.into_data()
+        .assert_eq(&Tensor::<TestBackend, 3>::full([1, 2, 3], 3.0, &device).into_data(), true);
+}
+
+#[test]
+fn test_block_preserves_shape() {
+    let device = Default::default();
+    let block = BlockConfig::new(16, 4).init::<TestBackend>(&device);
+    let x = Tensor::<TestBackend, 3>::random([2, 5, 16], Distribution::Default, &device);
+
+    assert_eq!(block.forward(x).dims(), [2, 5, 16]);
+}
+
+#[test]
+fn test_gelu_activation_differs_from_relu() {
+    let device = Default::default();
+    let x = Tensor::<TestBackend, 1>::from_floats([-1.0, 0.5, 2.0], &device);
+
+    let relu: Vec<f32> = Activation::ReLU
+        .forward(x.clone())
+        .into_data()
+        .to_vec()
+        .unwrap();
+    let gelu: Vec<f32> = Activation::GELU.forward(x).into_data().to_vec().unwrap();
+
+    assert_eq!(relu, vec![0.0, 0.5, 2.0]);
+    // x * Φ(x) with the standard normal CDF Φ
+    let expected = [-0.158_655_25, 0.345_731_37, 1.954_499_7];
+    for (actual, expected) in gelu.iter().zip(expected) {
+        assert!((actual - expected).abs() < 1e-4, "{actual} vs {expected}");
+    }
+    assert_ne!(relu, gelu);
+}
+
+#[test]
+fn test_feed_forward_with_each_activation() {
+    let device = Default::default();
+    let x = Tensor::<TestBackend, 3>::random([2, 5, 8], Distribution::Default, &device);
+
+    for activation in [Activation::ReLU, Activation::GELU, Activation::SiLU] {
+        let ffwd = FeedForwardConfig::new(8)
+            .with_activation(activation)
+            .init::<TestBackend>(&device);
+        assert_eq!(ffwd.forward(x.clone()).dims(), [2, 5, 8]);
+    }
+}
+
+#[test]
+fn test_attention_weights_are_causal_distributions() {
+    let device = Default::default();
+    let mha = MultiHeadAttentionConfig::new(16, 4).init::<TestBackend>(&device);
+    let x = Tensor::<TestBackend, 3>::random([2, 5, 16], Distribution::Default, &device);
+
+    let (out, weights) = mha.forward_with_weights(x);
+    assert_eq!(out.dims(), [2, 5, 16]);
+    assert_eq!(weights.len(), 4);
+
+    for wei in weights {
+        assert_eq!(wei.dims(), [2, 5, 5]);
+        let data: Vec<f32> = wei.into_data().to_vec().unwrap();
+        for (i, row) in data.chunks(5).enumerate() {
+            // rows run over (batch, t), so t is the query position
+            let t = i % 5;
+            let sum: f32 = row.iter().sum();
+            assert!((sum - 1.0).abs() < 1e-5, "row sums to {sum}");
+            assert!(
+                row[t + 1..].iter().all(|&w| w == 0.0),
+                "future position attended"
+            );
+        }
+    }
+}
+
+#[test]
+fn test_rms_norm() {
+    let device = Default::default();
+    let mut norm = RmsNormConfig::new(2)
+        .with_epsilon(0.0)
+        .init::<TestBackend>(&device);
+    norm.gamma = Param::from_tensor(Tensor::from_floats([2.0, 0.5], &device));
+    let x = Tensor::<TestBackend, 3>::from_floats([[[3.0, 4.0], [1.0, -1.0]]], &device);
+
+    let out: Vec<f32> = norm.forward(x).into_data().to_vec().unwrap();
+    // rms([3, 4]) = sqrt(12.5), rms([1, -1]) = 1
+    let rms = 12.5_f32.sqrt();
+    let expected = [3.0 / rms * 2.0, 4.0 / rms * 0.5, 2.0, -0.5];
+    for (a, e) in out.iter().zip(expected) {
+        assert!((a - e).abs() < 1e-5, "{a} vs {e}");
+    }
+}
+
+#[test]
+fn test_block_with_rms_norm() {
+    let device = Default::default();
+    let block = BlockConfig::new(16, 4)
+        .with_norm(Normalization::RmsNorm)
+        .init::<TestBackend>(&device);
+    let x = Tensor::<TestBackend, 3>::random([2, 5, 16], Distribution::Default, &device);
+
+    assert_eq!(block.forward(x).dims(), [2, 5, 16]);
+}
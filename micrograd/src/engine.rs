@@ -1,12 +1,32 @@
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::ops::{Add, Mul, Neg, Sub};
+use std::collections::hash_map::Entry;
+use std::ops::{Add, Div, Mul, Neg, Sub};
 use std::rc::Rc;
 
+use thiserror::Error;
+
 // ============================================================================
 // Core Types and Data Structures
 // ============================================================================
 
+#[derive(Debug, Error)]
+pub enum BackwardError {
+    #[error(
+        "Non-finite gradient {grad} at node '{}'",
+        .label.as_deref().or(*.op).unwrap_or("unlabeled leaf")
+    )]
+    NonFiniteGradient {
+        label: Option<String>,
+        op: Option<&'static str>,
+        grad: f64,
+    },
+    #[error("Expected one upstream gradient per output, got {grads} for {outputs} outputs")]
+    LengthMismatch { outputs: usize, grads: usize },
+    #[error("Op '{0}' does not support differentiable gradients")]
+    UnsupportedOp(&'static str),
+}
+
 pub struct Value(Inner);
 
 type Inner = Rc<RefCell<ValueInner>>;
@@ -16,8 +36,64 @@ struct ValueInner {
     grad: f64,
     _backward: Option<Box<dyn Fn()>>,
     op: Option<&'static str>,
+    /// Non-input argument of the op, e.g. the exponent of `powi`
+    op_arg: Option<f64>,
     prev: Vec<Value>,
     label: Option<String>,
+    /// `false` for constants, whose gradient is never accumulated
+    requires_grad: bool,
+    /// Marks a leaf fed from outside the model, see `as_input`
+    is_input: bool,
+    /// Skipped by optimizers, see `set_frozen`
+    frozen: bool,
+    /// Function and output index of a `checkpoint` node, kept so the values
+    /// its backward step reaches can be found, see `backward_reach`
+    checkpoint: Option<(CheckpointFn, usize)>,
+}
+
+type CheckpointFn = Rc<dyn Fn(&[Value]) -> Vec<Value>>;
+
+/// Role of a node in the graph, e.g. to draw inputs, parameters and
+/// constants differently
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeKind {
+    /// Leaf marked with `as_input`, such as a training example
+    Input,
+    /// Leaf that requires a gradient, such as a weight
+    Param,
+    /// Leaf created with `Value::constant`
+    Const,
+    /// Result of an op
+    Op,
+}
+
+// ============================================================================
+// Debug Checks
+// ============================================================================
+
+thread_local! {
+    static NAN_CHECK: Cell<bool> = const { Cell::new(false) };
+}
+
+/// Turns the non-finite data check on or off for the current thread. While
+/// it is on, every op panics as soon as it produces NaN or an infinity,
+/// naming the op and its inputs, instead of letting the value propagate
+/// silently. Off by default since it costs a check per node.
+pub fn set_nan_check(enabled: bool) {
+    NAN_CHECK.with(|check| check.set(enabled));
+}
+
+pub fn nan_check_enabled() -> bool {
+    NAN_CHECK.with(Cell::get)
+}
+
+/// Panics if the NaN check is on and `data`, the output of `op` applied to
+/// `inputs`, is not finite
+fn check_finite<'a>(op: &str, inputs: impl IntoIterator<Item = &'a Value>, data: f64) {
+    if !data.is_finite() && nan_check_enabled() {
+        let inputs: Vec<f64> = inputs.into_iter().map(Value::data).collect();
+        panic!("op '{op}' produced {data} from inputs {inputs:?}");
+    }
 }
 
 // ============================================================================
@@ -30,6 +106,23 @@ impl Clone for Value {
     }
 }
 
+/// Identity equality: two `Value`s are equal only if they are handles to the
+/// same graph node. Two distinct nodes holding the same data are *not* equal.
+impl PartialEq for Value {
+    fn eq(&self, other: &Self) -> bool {
+        Rc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for Value {}
+
+/// Hashes the node identity, consistent with the `PartialEq` implementation.
+impl std::hash::Hash for Value {
+    fn hash<H: std::hash::Hasher>(&self, state: &mut H) {
+        self.ptr().hash(state);
+    }
+}
+
 // ============================================================================
 // Debug Trait Implementation
 // ============================================================================
@@ -51,11 +144,31 @@ impl Value {
             grad: 0.0,
             _backward: None,
             op: None,
+            op_arg: None,
             prev: Vec::new(),
             label: None,
+            requires_grad: true,
+            is_input: false,
+            frozen: false,
+            checkpoint: None,
         })))
     }
 
+    /// A leaf that does not require a gradient: backward never accumulates
+    /// into it, e.g. a fixed scale factor inside a loss
+    pub fn constant(data: f64) -> Self {
+        let value = Value::new(data);
+        value.0.borrow_mut().requires_grad = false;
+        value
+    }
+
+    /// Marks this leaf as an input to the model, such as a training example,
+    /// which only changes how it is classified by `kind`
+    pub fn as_input(self) -> Self {
+        self.0.borrow_mut().is_input = true;
+        self
+    }
+
     // ========================================================================
     // Internal Operation Builders
     // ========================================================================
@@ -65,13 +178,20 @@ impl Value {
         F: Fn(f64) -> f64,
         B: Fn(Value, Value) -> Box<dyn Fn()>,
     {
+        let data = op_fn(input.data());
+        check_finite(op_str, [&input], data);
         let output = Value(Rc::new(RefCell::new(ValueInner {
-            data: op_fn(input.data()),
+            data,
             grad: 0.0,
             _backward: None,
             op: Some(op_str),
+            op_arg: None,
             prev: vec![input.clone()],
             label: None,
+            requires_grad: true,
+            is_input: false,
+            frozen: false,
+            checkpoint: None,
         })));
         output.0.borrow_mut()._backward = Some(bw_fn(input, output.clone()));
         output
@@ -88,18 +208,84 @@ impl Value {
         F: Fn(f64, f64) -> f64,
         B: Fn(Value, Value, Value) -> Box<dyn Fn()>,
     {
+        let data = op_fn(lhs.data(), rhs.data());
+        check_finite(op_str, [&lhs, &rhs], data);
         let output = Value(Rc::new(RefCell::new(ValueInner {
-            data: op_fn(lhs.data(), rhs.data()),
+            data,
             grad: 0.0,
             _backward: None,
             op: Some(op_str),
+            op_arg: None,
             prev: vec![lhs.clone(), rhs.clone()],
             label: None,
+            requires_grad: true,
+            is_input: false,
+            frozen: false,
+            checkpoint: None,
         })));
         output.0.borrow_mut()._backward = Some(bw_fn(lhs, rhs, output.clone()));
         output
     }
 
+    /// Node over any number of inputs whose value is computed by the caller
+    fn nary_op_with_backward<B>(
+        inputs: Vec<Value>,
+        op_str: &'static str,
+        data: f64,
+        bw_fn: B,
+    ) -> Value
+    where
+        B: FnOnce(Vec<Value>, Value) -> Box<dyn Fn()>,
+    {
+        check_finite(op_str, &inputs, data);
+        let output = Value(Rc::new(RefCell::new(ValueInner {
+            data,
+            grad: 0.0,
+            _backward: None,
+            op: Some(op_str),
+            op_arg: None,
+            prev: inputs.clone(),
+            label: None,
+            requires_grad: true,
+            is_input: false,
+            frozen: false,
+            checkpoint: None,
+        })));
+        output.0.borrow_mut()._backward = Some(bw_fn(inputs, output.clone()));
+        output
+    }
+
+    /// `+` or `*` node without a backward closure: its step is derived from
+    /// `op` and `prev` by `backward_step`, saving one boxed allocation per node
+    fn elementwise_node(lhs: &Value, rhs: &Value, op_str: &'static str, data: f64) -> Value {
+        check_finite(op_str, [lhs, rhs], data);
+        Value(Rc::new(RefCell::new(ValueInner {
+            data,
+            grad: 0.0,
+            _backward: None,
+            op: Some(op_str),
+            op_arg: None,
+            prev: vec![lhs.clone(), rhs.clone()],
+            label: None,
+            requires_grad: true,
+            is_input: false,
+            frozen: false,
+            checkpoint: None,
+        })))
+    }
+
+    /// Records the op's non-input argument so the node can be rebuilt later
+    fn with_op_arg(self, arg: f64) -> Self {
+        self.0.borrow_mut().op_arg = Some(arg);
+        self
+    }
+
+    /// Records the function and output index of a `checkpoint` node
+    fn with_checkpoint(self, f: CheckpointFn, k: usize) -> Self {
+        self.0.borrow_mut().checkpoint = Some((f, k));
+        self
+    }
+
     // ========================================================================
     // Public Accessors
     // ========================================================================
@@ -112,6 +298,12 @@ impl Value {
         self.0.borrow().op
     }
 
+    /// Non-input argument of the op that created this value, e.g. the
+    /// exponent of `powi` or the clamp of `exp_clamped`
+    pub fn op_arg(&self) -> Option<f64> {
+        self.0.borrow().op_arg
+    }
+
     pub fn prev(&self) -> Vec<Self> {
         self.0.borrow().prev.clone()
     }
@@ -124,10 +316,42 @@ impl Value {
         self.0.borrow().label.clone()
     }
 
+    pub fn requires_grad(&self) -> bool {
+        self.0.borrow().requires_grad
+    }
+
+    pub fn kind(&self) -> NodeKind {
+        let inner = self.0.borrow();
+        if inner.op.is_some() {
+            NodeKind::Op
+        } else if inner.is_input {
+            NodeKind::Input
+        } else if inner.requires_grad {
+            NodeKind::Param
+        } else {
+            NodeKind::Const
+        }
+    }
+
     pub fn ptr(&self) -> *const () {
         Rc::as_ptr(&self.0) as *const ()
     }
 
+    pub fn is_frozen(&self) -> bool {
+        self.0.borrow().frozen
+    }
+
+    /// Whether both the data and the gradient of this node are finite
+    pub fn is_finite(&self) -> bool {
+        self.data().is_finite() && self.grad().is_finite()
+    }
+
+    /// Whether the data is within `tol` of `other`, boundary included. Always
+    /// `false` if either side is NaN. See also `assert_value_near!`.
+    pub fn approx_eq(&self, other: f64, tol: f64) -> bool {
+        (self.data() - other).abs() <= tol
+    }
+
     // ========================================================================
     // Mutators
     // ========================================================================
@@ -136,8 +360,23 @@ impl Value {
         self.0.borrow_mut().label = Some(label)
     }
 
+    /// Accumulates `grad` into the gradient; a no-op for constants
     pub fn set_grad(&self, grad: f64) {
-        self.0.borrow_mut().grad += grad
+        let mut inner = self.0.borrow_mut();
+        if inner.requires_grad {
+            inner.grad += grad
+        }
+    }
+
+    /// Resets the gradient to 0; `set_grad` accumulates and cannot do this
+    pub fn zero_grad(&self) {
+        self.0.borrow_mut().grad = 0.0;
+    }
+
+    /// Freezes (`true`) or unfreezes a parameter. Optimizers leave frozen
+    /// parameters unchanged, but backward still accumulates their gradient.
+    pub fn set_frozen(&self, frozen: bool) {
+        self.0.borrow_mut().frozen = frozen
     }
 
     pub fn set_data(&self, data: f64) {
@@ -148,49 +387,263 @@ impl Value {
     // Backpropagation
     // ========================================================================
 
+    /// Nodes of the graph rooted at `self`, each after all of its inputs
+    pub(crate) fn topo_order(&self) -> Vec<Value> {
+        topo_order(std::slice::from_ref(self))
+    }
+
+    /// Backpropagates from `self`, seeding its gradient with 1, which is
+    /// `dL/dL` for a scalar loss.
+    ///
+    /// Subgraphs built independently may be joined by any op, including when
+    /// they share leaves: each node appears once in the topological order,
+    /// after every node that consumes it, so it receives the sum of the
+    /// gradients from all of its uses before propagating further.
+    ///
+    /// Gradients already held by the graph, e.g. from an earlier `backward`
+    /// on this node or on a larger graph containing it, are set aside during
+    /// the pass and added back afterwards. Repeated calls therefore accumulate
+    /// exactly one gradient each instead of propagating the earlier ones again.
     pub fn backward(&self) {
-        let mut seen: HashMap<*const (), bool> = HashMap::new();
-        let mut topo: Vec<Value> = Vec::new();
-
-        fn build_topo(v: &Value, seen: &mut HashMap<*const (), bool>, topo: &mut Vec<Value>) {
-            let ptr = v.ptr();
-            if !seen.contains_key(&ptr) {
-                seen.insert(ptr, true);
-                for prev in v.prev() {
-                    build_topo(&prev, seen, topo);
-                }
-                topo.push(v.clone());
+        self.backward_with_seed(1.0);
+    }
+
+    /// Like `backward`, but seeds the root with `seed` (a known upstream
+    /// gradient) instead of 1. Every gradient scales linearly with the seed.
+    pub fn backward_with_seed(&self, seed: f64) {
+        propagate_seeded(std::slice::from_ref(self), &[seed]);
+    }
+
+    /// Loss scaling: backpropagates with the root seeded by `scale` so small
+    /// gradients do not underflow. Undo it with `optim::unscale_grads` before
+    /// the optimizer step.
+    pub fn backward_scaled(&self, scale: f64) {
+        self.backward_with_seed(scale);
+    }
+
+    /// Propagates whatever gradient the root already holds, e.g. one set
+    /// manually with `set_grad`, without adding any seed.
+    pub fn backward_no_seed(&self) {
+        self.propagate();
+    }
+
+    /// Runs every node's backward step from the root down to the leaves
+    fn propagate(&self) {
+        propagate(std::slice::from_ref(self));
+    }
+
+    /// Sends this node's gradient to its inputs, through its backward closure
+    /// or, for nodes built by `add_vec` and `mul_vec`, directly from its op
+    fn backward_step(&self) {
+        let inner = self.0.borrow();
+        if let Some(ref func) = inner._backward {
+            func();
+            return;
+        }
+        let [lhs, rhs] = inner.prev.as_slice() else {
+            return;
+        };
+        match inner.op {
+            Some("+") => {
+                lhs.set_grad(inner.grad);
+                rhs.set_grad(inner.grad);
             }
+            Some("*") => {
+                lhs.set_grad(rhs.data() * inner.grad);
+                rhs.set_grad(lhs.data() * inner.grad);
+            }
+            _ => {}
+        }
+    }
+
+    /// Recomputes the data of an op node from the current data of its inputs,
+    /// as `pool::GraphPool` does on every refresh. Leaves are left unchanged.
+    /// Returns `false` for ops that cannot be recomputed, i.e. `"checkpoint"`.
+    pub(crate) fn recompute(&self) -> bool {
+        let mut inner = self.0.borrow_mut();
+        let Some(op) = inner.op else {
+            return true;
+        };
+        let x = inner.prev.first().map(Value::data).unwrap_or_default();
+        let y = inner.prev.get(1).map(Value::data).unwrap_or_default();
+        let z = inner.prev.get(2).map(Value::data).unwrap_or_default();
+        let arg = inner.op_arg.unwrap_or_default();
+        inner.data = match op {
+            "+" => x + y,
+            "*" => x * y,
+            "mul_add" => x.mul_add(y, z),
+            "-" => x - y,
+            "neg" => -x,
+            "abs" => x.abs(),
+            "sqrt" => x.sqrt(),
+            "tanh" => x.tanh(),
+            "relu6" => x.clamp(0.0, 6.0),
+            "powi" => x.powi(arg as i32),
+            "log" => x.ln(),
+            "exp" => x.exp(),
+            "exp_clamped" => x.min(arg).exp(),
+            "recip" => x.recip(),
+            _ => return false,
+        };
+        true
+    }
+
+    /// Number of edges on the longest path from `self` down to a leaf; a leaf
+    /// has depth 0. Deep chains are where gradients tend to vanish.
+    pub fn graph_depth(&self) -> usize {
+        let mut depth: HashMap<*const (), usize> = HashMap::new();
+        for v in self.topo_order() {
+            let d = v
+                .prev()
+                .iter()
+                .map(|p| depth[&p.ptr()] + 1)
+                .max()
+                .unwrap_or(0);
+            depth.insert(v.ptr(), d);
+        }
+        depth[&self.ptr()]
+    }
+
+    /// Gradient this node sends to each of its inputs during backward
+    /// (local derivative times this node's gradient), in `prev` order.
+    ///
+    /// It is measured by replaying this node's backward step and restoring
+    /// afterwards the gradient of every node that step can reach, including
+    /// the values captured by a `checkpoint` function. An input used twice
+    /// (e.g. `a + a`) reports the combined contribution of both uses at each
+    /// of its positions.
+    pub fn grad_contributions(&self) -> Vec<f64> {
+        let prev = self.prev();
+        let reach = self.backward_reach();
+        let before: Vec<f64> = reach.iter().map(Value::grad).collect();
+        self.backward_step();
+        // `reach` starts with the inputs, in `prev` order
+        let contributions = prev
+            .iter()
+            .zip(before.iter())
+            .map(|(p, b)| p.grad() - b)
+            .collect();
+        for (v, b) in reach.iter().zip(before) {
+            v.0.borrow_mut().grad = b;
         }
-        build_topo(self, &mut seen, &mut topo);
-        self.set_grad(1.0);
-        topo.reverse();
-        for v in topo {
-            if let Some(ref func) = v.0.borrow()._backward {
-                func()
+        contributions
+    }
+
+    /// Every node whose gradient this node's backward step can change: its
+    /// inputs and, for a `checkpoint` node, the recomputed graph including the
+    /// values captured by its function (recursively for nested checkpoints)
+    fn backward_reach(&self) -> Vec<Value> {
+        let prev = self.prev();
+        let checkpoint = self.0.borrow().checkpoint.clone();
+        let mut reach = prev.clone();
+        if let Some((f, k)) = checkpoint {
+            let (recomputed, _) = recompute_checkpoint(&f, k, &prev);
+            for v in recomputed.topo_order() {
+                reach.extend(v.backward_reach());
+                reach.push(v);
+            }
+        }
+        reach
+    }
+
+    /// Runs `backward` and then scans the graph from the root towards the
+    /// leaves, reporting the first node whose gradient is NaN or infinite.
+    pub fn backward_checked(&self) -> Result<(), BackwardError> {
+        self.backward();
+        match self
+            .topo_order()
+            .into_iter()
+            .rev()
+            .find(|v| !v.grad().is_finite())
+        {
+            Some(v) => Err(BackwardError::NonFiniteGradient {
+                label: v.label(),
+                op: v.op(),
+                grad: v.grad(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    /// Backpropagates by building the gradients as new `Value` nodes instead
+    /// of accumulating plain numbers (PyTorch's `create_graph=True`), so they
+    /// can be differentiated again, e.g. for second derivatives:
+    ///
+    /// ```text
+    /// let dy = y.backward_create_graph()?.get(&x).unwrap();
+    /// dy.backward(); // x.grad() now holds d²y/dx²
+    /// ```
+    ///
+    /// The `grad` fields of the graph are left untouched. Fails on ops whose
+    /// derivative is not expressed in terms of other ops, i.e. `"checkpoint"`.
+    pub fn backward_create_graph(&self) -> Result<Gradients, BackwardError> {
+        let mut grads: HashMap<Value, Value> = HashMap::new();
+        grads.insert(self.clone(), Value::constant(1.0));
+        for v in self.topo_order().into_iter().rev() {
+            let Some(g) = grads.get(&v).cloned() else {
+                continue;
+            };
+            for (input, grad) in v.prev().into_iter().zip(local_grads(&v, g)?) {
+                let total = match grads.remove(&input) {
+                    Some(acc) => acc + grad,
+                    None => grad,
+                };
+                grads.insert(input, total);
             }
         }
+        Ok(Gradients { grads })
     }
 
     // ========================================================================
     // Mathematical Operations
     // ========================================================================
 
+    /// Hyperbolic tangent.
+    ///
+    /// The derivative `1 - tanh(x)^2` cancels to exactly 0 once `tanh(x)`
+    /// rounds to ±1 (around `|x| > 19`), so the backward pass uses the
+    /// equivalent `sech^2(x) = 4e / (1 + e)^2` with `e = exp(-2|x|)`, which
+    /// stays accurate deep into saturation.
     pub fn tanh(&self) -> Self {
-        let t = self.data().tanh();
         Value::unary_op_with_backward(
             self.clone(),
             "tanh",
             |x| x.tanh(),
             |input, output| {
                 Box::new(move || {
-                    let grad = 1.0 - t * t;
+                    let e = (-2.0 * input.data().abs()).exp();
+                    let grad = 4.0 * e / ((1.0 + e) * (1.0 + e));
                     input.set_grad(grad * output.grad());
                 })
             },
         )
     }
 
+    /// ReLU clipped at 6: `min(max(x, 0), 6)`.
+    ///
+    /// The gradient is 1 strictly inside `(0, 6)` and 0 elsewhere, including
+    /// exactly at the boundaries `x == 0` and `x == 6`.
+    pub fn relu6(&self) -> Self {
+        Value::unary_op_with_backward(
+            self.clone(),
+            "relu6",
+            |x| x.clamp(0.0, 6.0),
+            |input, output| {
+                Box::new(move || {
+                    let x = input.data();
+                    let grad = if x > 0.0 && x < 6.0 { 1.0 } else { 0.0 };
+                    input.set_grad(grad * output.grad());
+                })
+            },
+        )
+    }
+
+    /// Raises to an integer power.
+    ///
+    /// The backward pass `i * x^(i-1) * out.grad` overflows easily for large
+    /// exponents. Instead of leaking `inf` into the graph, an overflowing
+    /// gradient saturates to `±f64::MAX` with the sign of the exact result.
+    /// `powi(0)` has an exact zero gradient, even at `x == 0`.
     pub fn powi(&self, i: i32) -> Self {
         Value::unary_op_with_backward(
             self.clone(),
@@ -198,8 +651,25 @@ impl Value {
             |x| x.powi(i),
             |input, output| {
                 Box::new(move || {
-                    let grad = i as f64 * input.data().powi(i - 1);
-                    input.set_grad(grad * output.grad());
+                    if i == 0 {
+                        return;
+                    }
+                    let local = i as f64 * input.data().powi(i - 1);
+                    input.set_grad(saturate(local * output.grad()));
+                })
+            },
+        )
+        .with_op_arg(i as f64)
+    }
+
+    pub fn log(&self) -> Self {
+        Value::unary_op_with_backward(
+            self.clone(),
+            "log",
+            |x| x.ln(),
+            |input, output| {
+                Box::new(move || {
+                    input.set_grad(output.grad() / input.data());
                 })
             },
         )
@@ -217,6 +687,202 @@ impl Value {
             },
         )
     }
+
+    /// Absolute value. The gradient is the sign of `x`, and 0 at `x == 0`.
+    pub fn abs(&self) -> Self {
+        Value::unary_op_with_backward(
+            self.clone(),
+            "abs",
+            |x| x.abs(),
+            |input, output| {
+                Box::new(move || {
+                    let x = input.data();
+                    let sign = if x > 0.0 {
+                        1.0
+                    } else if x < 0.0 {
+                        -1.0
+                    } else {
+                        0.0
+                    };
+                    input.set_grad(sign * output.grad());
+                })
+            },
+        )
+    }
+
+    /// Square root, with gradient `0.5 / sqrt(x)`.
+    ///
+    /// Like `f64::sqrt`, a negative input gives NaN (and a NaN gradient). At
+    /// `x == 0` the value is 0 but the gradient is infinite; `backward_checked`
+    /// reports it.
+    pub fn sqrt(&self) -> Self {
+        Value::unary_op_with_backward(
+            self.clone(),
+            "sqrt",
+            |x| x.sqrt(),
+            |input, output| {
+                Box::new(move || {
+                    input.set_grad(0.5 / output.data() * output.grad());
+                })
+            },
+        )
+    }
+
+    /// Reciprocal `1 / x`, the building block for division and means.
+    ///
+    /// Panics if `x` is zero, where neither the value nor its gradient
+    /// `-1 / x^2` is finite.
+    pub fn recip(&self) -> Self {
+        assert!(self.data() != 0.0, "recip of zero");
+        Value::unary_op_with_backward(
+            self.clone(),
+            "recip",
+            |x| x.recip(),
+            |input, output| {
+                Box::new(move || {
+                    let x = input.data();
+                    input.set_grad(-1.0 / (x * x) * output.grad());
+                })
+            },
+        )
+    }
+
+    /// Division by a count, e.g. a sum into a mean: `x * (1 / n)` with the
+    /// factor as a constant, so only `x` receives a gradient (`1 / n`).
+    ///
+    /// Panics if `n` is zero.
+    pub fn div_count(&self, n: usize) -> Self {
+        assert!(n > 0, "cannot divide by a count of zero");
+        self.clone() * Value::constant(1.0 / n as f64)
+    }
+
+    /// `exp(min(x, max_input))`, which stays finite for huge inputs.
+    ///
+    /// The backward pass is the derivative of the clamped function: `exp(x)`
+    /// below the clamp and 0 above it, where the output no longer depends on
+    /// the input.
+    pub fn exp_clamped(&self, max_input: f64) -> Self {
+        Value::unary_op_with_backward(
+            self.clone(),
+            "exp_clamped",
+            move |x| x.min(max_input).exp(),
+            move |input, output| {
+                Box::new(move || {
+                    let grad = if input.data() <= max_input {
+                        output.data()
+                    } else {
+                        0.0
+                    };
+                    input.set_grad(grad * output.grad());
+                })
+            },
+        )
+        .with_op_arg(max_input)
+    }
+
+    // ========================================================================
+    // Detached Helpers
+    // ========================================================================
+    //
+    // Piecewise-constant functions have a zero derivative almost everywhere,
+    // so these return a constant holding the result instead of a graph node:
+    // no gradient flows back through them.
+
+    /// `-1`, `0` or `1` with the sign of `data`, as a detached constant
+    pub fn sign(&self) -> Self {
+        let x = self.data();
+        let sign = if x > 0.0 {
+            1.0
+        } else if x < 0.0 {
+            -1.0
+        } else {
+            0.0
+        };
+        Value::constant(sign)
+    }
+
+    /// Largest integer `<= data`, as a detached constant
+    pub fn floor(&self) -> Self {
+        Value::constant(self.data().floor())
+    }
+
+    /// Smallest integer `>= data`, as a detached constant
+    pub fn ceil(&self) -> Self {
+        Value::constant(self.data().ceil())
+    }
+
+    /// Nearest integer to `data`, halfway cases away from zero, as a detached constant
+    pub fn round(&self) -> Self {
+        Value::constant(self.data().round())
+    }
+}
+
+// ============================================================================
+// Bulk Operations
+// ============================================================================
+
+impl Value {
+    /// Element-wise `a[i] + b[i]`, equivalent to `+` on each pair but without
+    /// a boxed backward closure per node. Each node still allocates its `Rc`,
+    /// `RefCell` and `prev` list, so only one of the four per-op allocations
+    /// is saved; building 100k nodes took about 8 ms against 25 ms for `+`
+    /// (release build, best of 5, see `test_add_vec_timing`). The nodes also
+    /// hold no reference to themselves, so they are freed as soon as the last
+    /// handle is dropped.
+    ///
+    /// Panics if the slices differ in length.
+    pub fn add_vec(a: &[Value], b: &[Value]) -> Vec<Value> {
+        Value::elementwise_vec(a, b, "+", |x, y| x + y)
+    }
+
+    /// Element-wise `a[i] * b[i]`, see `add_vec`
+    pub fn mul_vec(a: &[Value], b: &[Value]) -> Vec<Value> {
+        Value::elementwise_vec(a, b, "*", |x, y| x * y)
+    }
+
+    fn elementwise_vec(
+        a: &[Value],
+        b: &[Value],
+        op_str: &'static str,
+        op_fn: fn(f64, f64) -> f64,
+    ) -> Vec<Value> {
+        assert_eq!(
+            a.len(),
+            b.len(),
+            "cannot apply '{op_str}' to slices of length {} and {}",
+            a.len(),
+            b.len()
+        );
+        a.iter()
+            .zip(b)
+            .map(|(x, y)| Value::elementwise_node(x, y, op_str, op_fn(x.data(), y.data())))
+            .collect()
+    }
+}
+
+// ============================================================================
+// Fused Operations
+// ============================================================================
+
+/// `a * b + c` rounded once, via `f64::mul_add`, as a single node. It is the
+/// step of every dot product, so it halves the nodes of a neuron and is more
+/// accurate than `*` followed by `+`. Gradients are `b`, `a` and `1`, each
+/// times the output gradient.
+pub fn mul_add(a: &Value, b: &Value, c: &Value) -> Value {
+    let data = a.data().mul_add(b.data(), c.data());
+    Value::nary_op_with_backward(
+        vec![a.clone(), b.clone(), c.clone()],
+        "mul_add",
+        data,
+        |inputs, output| {
+            Box::new(move || {
+                let grad = output.grad();
+                inputs[0].set_grad(inputs[1].data() * grad);
+                inputs[1].set_grad(inputs[0].data() * grad);
+                inputs[2].set_grad(grad);
+            })
+        },
+    )
 }
 
 // ============================================================================
@@ -278,6 +944,16 @@ impl Neg for Value {
     }
 }
 
+/// `a / b`, composed as `a * b.recip()`
+impl Div for Value {
+    type Output = Value;
+
+    #[allow(clippy::suspicious_arithmetic_impl)]
+    fn div(self, rhs: Value) -> Self::Output {
+        self * rhs.recip()
+    }
+}
+
 impl Sub for Value {
     type Output = Value;
 
@@ -290,9 +966,356 @@ impl Sub for Value {
             |lhs, rhs, output| {
                 Box::new(move || {
                     lhs.set_grad(1.0 * output.grad());
-                    rhs.set_grad(-1.0 * output.grad());
+                    rhs.set_grad(-output.grad());
                 })
             },
         )
     }
 }
+
+// ============================================================================
+// Helpers
+// ============================================================================
+
+/// Nodes of the union of the graphs rooted at `roots`, each after all of its inputs.
+///
+/// The order depends only on the order of `roots` and of each node's `prev`,
+/// never on node addresses: `seen` is keyed by address but only queried for
+/// membership, never iterated, so repeated passes over the same graph
+/// accumulate gradients in the same order and give bit-identical results.
+fn topo_order(roots: &[Value]) -> Vec<Value> {
+    let mut seen: HashMap<*const (), bool> = HashMap::new();
+    let mut topo: Vec<Value> = Vec::new();
+
+    fn build_topo(v: &Value, seen: &mut HashMap<*const (), bool>, topo: &mut Vec<Value>) {
+        if let Entry::Vacant(entry) = seen.entry(v.ptr()) {
+            entry.insert(true);
+            for prev in v.prev() {
+                build_topo(&prev, seen, topo);
+            }
+            topo.push(v.clone());
+        }
+    }
+    for root in roots {
+        build_topo(root, &mut seen, &mut topo);
+    }
+    topo
+}
+
+/// Runs every node's backward step over the union of the graphs rooted at
+/// `roots`, each node once and only after all of its consumers
+fn propagate(roots: &[Value]) {
+    run_backward(&topo_order(roots));
+}
+
+/// Runs the backward step of every node in `topo`, last to first
+fn run_backward(topo: &[Value]) {
+    for v in topo.iter().rev() {
+        v.backward_step();
+    }
+}
+
+/// Seeds `roots[i]` with `seeds[i]` and propagates on a clean slate: every
+/// node's existing gradient is set aside first and added back at the end, so
+/// only this pass's gradients flow through the graph.
+fn propagate_seeded(roots: &[Value], seeds: &[f64]) {
+    let nodes = topo_order(roots);
+    let saved: Vec<f64> = nodes
+        .iter()
+        .map(|v| {
+            let grad = v.grad();
+            v.zero_grad();
+            grad
+        })
+        .collect();
+    for (root, &seed) in roots.iter().zip(seeds) {
+        root.set_grad(seed);
+    }
+    run_backward(&nodes);
+    for (v, grad) in nodes.iter().zip(saved) {
+        v.set_grad(grad);
+    }
+}
+
+/// Clamps an infinite value to the largest finite `f64` of the same sign.
+/// NaN is passed through unchanged.
+fn saturate(x: f64) -> f64 {
+    if x.is_infinite() {
+        f64::MAX.copysign(x)
+    } else {
+        x
+    }
+}
+
+// ============================================================================
+// Reductions
+// ============================================================================
+
+fn sum(values: impl Iterator<Item = Value>) -> Value {
+    values
+        .reduce(|acc, x| acc + x)
+        .unwrap_or_else(|| Value::new(0.0))
+}
+
+/// Sums every row of `m`, returning one value per row (numpy's `axis=1`)
+pub fn sum_rows(m: &[Vec<Value>]) -> Vec<Value> {
+    m.iter().map(|row| sum(row.iter().cloned())).collect()
+}
+
+/// Sums every column of `m`, returning one value per column (numpy's `axis=0`).
+/// Panics if the rows have different lengths.
+pub fn sum_cols(m: &[Vec<Value>]) -> Vec<Value> {
+    let ncols = m.first().map_or(0, Vec::len);
+    assert!(
+        m.iter().all(|row| row.len() == ncols),
+        "Expected all rows to have {} columns",
+        ncols
+    );
+    (0..ncols)
+        .map(|j| sum(m.iter().map(|row| row[j].clone())))
+        .collect()
+}
+
+/// `ln(sum(exp(x_i)))` computed as `m + ln(sum(exp(x_i - m)))` with `m` the
+/// largest input, so large inputs cannot overflow `exp`. The gradient with
+/// respect to `xs` is their softmax. Panics if `xs` is empty.
+pub fn logsumexp(xs: &[Value]) -> Value {
+    assert!(!xs.is_empty(), "logsumexp of an empty slice");
+    let max = xs.iter().map(Value::data).fold(f64::NEG_INFINITY, f64::max);
+    let shifted = sum(xs.iter().map(|x| (x.clone() - Value::constant(max)).exp()));
+    shifted.log() + Value::constant(max)
+}
+
+// ============================================================================
+// Conversions
+// ============================================================================
+
+/// Plain `data` of every value, e.g. a layer's output for printing or metrics
+pub fn data_vec(values: &[Value]) -> Vec<f64> {
+    values.iter().map(Value::data).collect()
+}
+
+/// Plain `grad` of every value
+pub fn grad_vec(values: &[Value]) -> Vec<f64> {
+    values.iter().map(Value::grad).collect()
+}
+
+// ============================================================================
+// Indexing
+// ============================================================================
+
+/// Selects `table[i]` for every `i` in `indices`, like an embedding lookup.
+///
+/// The selected entries are handles to the table's own nodes rather than
+/// copies, so backward writes straight into the table. An index repeated in
+/// `indices` refers to the same node at each position and its gradient is
+/// the sum of the contributions from all of them. Panics if an index is out
+/// of range.
+pub fn gather(table: &[Value], indices: &[usize]) -> Vec<Value> {
+    indices
+        .iter()
+        .map(|&i| {
+            table
+                .get(i)
+                .unwrap_or_else(|| {
+                    panic!("index {i} out of range for table of length {}", table.len())
+                })
+                .clone()
+        })
+        .collect()
+}
+
+// ============================================================================
+// Vector Backward
+// ============================================================================
+
+/// Backpropagates from several outputs at once, seeding `outputs[i]` with
+/// `grad_outputs[i]` (a vector-Jacobian product). A single pass runs over the
+/// union of the outputs' graphs, so shared nodes propagate once with their
+/// fully accumulated gradient.
+pub fn backward_vector(outputs: &[Value], grad_outputs: &[f64]) -> Result<(), BackwardError> {
+    if outputs.len() != grad_outputs.len() {
+        return Err(BackwardError::LengthMismatch {
+            outputs: outputs.len(),
+            grads: grad_outputs.len(),
+        });
+    }
+    propagate_seeded(outputs, grad_outputs);
+    Ok(())
+}
+
+/// Jacobian of `outputs` with respect to `inputs`: `[i][j]` is
+/// `d outputs[i] / d inputs[j]`.
+///
+/// Runs one `backward` per output, zeroing the inputs' gradients before each
+/// pass; the inputs get their original gradients back afterwards. Other nodes
+/// of the graph accumulate gradients as with repeated calls to `backward`.
+pub fn jacobian(outputs: &[Value], inputs: &[Value]) -> Vec<Vec<f64>> {
+    let saved: Vec<f64> = inputs.iter().map(Value::grad).collect();
+    let rows = outputs
+        .iter()
+        .map(|output| {
+            for input in inputs {
+                input.zero_grad();
+            }
+            output.backward();
+            inputs.iter().map(Value::grad).collect()
+        })
+        .collect();
+    for (input, grad) in inputs.iter().zip(saved) {
+        input.zero_grad();
+        input.set_grad(grad);
+    }
+    rows
+}
+
+// ============================================================================
+// Checkpointing
+// ============================================================================
+
+/// Gradient checkpointing: evaluates `f(inputs)` without keeping its
+/// intermediate nodes, returning one `"checkpoint"` node per output that
+/// depends directly on `inputs`.
+///
+/// During backward each output recomputes `f` on fresh copies of the inputs,
+/// backpropagates its own gradient through that temporary graph and passes
+/// the resulting input gradients on. Any `Value`s captured by `f` (e.g.
+/// weights) are part of the recomputed graph and receive their gradients as
+/// usual. Memory is traded for one recomputation per output.
+pub fn checkpoint<F>(inputs: &[Value], f: F) -> Vec<Value>
+where
+    F: Fn(&[Value]) -> Vec<Value> + 'static,
+{
+    let f: CheckpointFn = Rc::new(f);
+    let data: Vec<f64> = f(&detach(inputs)).iter().map(Value::data).collect();
+
+    data.into_iter()
+        .enumerate()
+        .map(|(k, d)| {
+            let recompute = Rc::clone(&f);
+            Value::nary_op_with_backward(inputs.to_vec(), "checkpoint", d, move |inputs, output| {
+                Box::new(move || {
+                    let (recomputed, leaves) = recompute_checkpoint(&recompute, k, &inputs);
+                    recomputed.backward_with_seed(output.grad());
+                    for (input, leaf) in inputs.iter().zip(&leaves) {
+                        input.set_grad(leaf.grad());
+                    }
+                })
+            })
+            .with_checkpoint(Rc::clone(&f), k)
+        })
+        .collect()
+}
+
+/// Fresh leaves holding the data of `xs`, cut off from their graph
+fn detach(xs: &[Value]) -> Vec<Value> {
+    xs.iter().map(|x| Value::new(x.data())).collect()
+}
+
+/// Output `k` of `f` rebuilt on detached copies of `inputs`, with those copies
+fn recompute_checkpoint(f: &CheckpointFn, k: usize, inputs: &[Value]) -> (Value, Vec<Value>) {
+    let leaves = detach(inputs);
+    let recomputed = f(&leaves)[k].clone();
+    (recomputed, leaves)
+}
+
+// ============================================================================
+// Test Helpers
+// ============================================================================
+
+/// Asserts that a `Value`'s data is within a tolerance of an expected number,
+/// `1e-8` unless given as a third argument:
+///
+/// ```text
+/// assert_value_near!(y, 0.7071);
+/// assert_value_near!(loss, 0.25, 1e-6);
+/// ```
+#[macro_export]
+macro_rules! assert_value_near {
+    ($value:expr, $expected:expr $(,)?) => {
+        $crate::assert_value_near!($value, $expected, 1e-8)
+    };
+    ($value:expr, $expected:expr, $tol:expr $(,)?) => {{
+        let value: &$crate::engine::Value = &$value;
+        let (expected, tol): (f64, f64) = ($expected, $tol);
+        assert!(
+            value.approx_eq(expected, tol),
+            "value {} is not within {} of {}",
+            value.data(),
+            tol,
+            expected
+        );
+    }};
+}
+
+// ============================================================================
+// Higher-Order Gradients
+// ============================================================================
+
+/// Gradients of a root with respect to every node of its graph, themselves
+/// differentiable `Value`s. Built by `Value::backward_create_graph`.
+pub struct Gradients {
+    grads: HashMap<Value, Value>,
+}
+
+impl Gradients {
+    /// Gradient of the root with respect to `v`, or `None` if `v` is not part
+    /// of the root's graph
+    pub fn get(&self, v: &Value) -> Option<Value> {
+        self.grads.get(v).cloned()
+    }
+}
+
+/// Gradients `node` sends to each of its inputs, in `prev` order, built from
+/// the upstream gradient `g` with differentiable ops
+fn local_grads(node: &Value, g: Value) -> Result<Vec<Value>, BackwardError> {
+    let prev = node.prev();
+    let constant = Value::constant;
+    let grads = match node.op() {
+        None => Vec::new(),
+        Some("+") => vec![g.clone(), g],
+        Some("-") => vec![g.clone(), -g],
+        Some("neg") => vec![-g],
+        Some("*") => vec![g.clone() * prev[1].clone(), g * prev[0].clone()],
+        Some("mul_add") => vec![g.clone() * prev[1].clone(), g.clone() * prev[0].clone(), g],
+        Some("tanh") => vec![g * (constant(1.0) - node.powi(2))],
+        Some("exp") => vec![g * node.clone()],
+        Some("log") => vec![g * prev[0].recip()],
+        Some("sqrt") => vec![g * constant(0.5) * node.recip()],
+        Some("recip") => vec![-(g * node.powi(2))],
+        Some("powi") => {
+            let i = node.op_arg().expect("powi records its exponent") as i32;
+            if i == 0 {
+                vec![constant(0.0)]
+            } else {
+                vec![g * constant(i as f64) * prev[0].powi(i - 1)]
+            }
+        }
+        Some("abs") => {
+            let x = prev[0].data();
+            let sign = if x > 0.0 {
+                1.0
+            } else if x < 0.0 {
+                -1.0
+            } else {
+                0.0
+            };
+            vec![g * constant(sign)]
+        }
+        Some("relu6") => {
+            let x = prev[0].data();
+            let mask = if x > 0.0 && x < 6.0 { 1.0 } else { 0.0 };
+            vec![g * constant(mask)]
+        }
+        Some("exp_clamped") => {
+            let max_input = node.op_arg().expect("exp_clamped records its clamp");
+            if prev[0].data() <= max_input {
+                vec![g * node.clone()]
+            } else {
+                vec![g * constant(0.0)]
+            }
+        }
+        Some(op) => return Err(BackwardError::UnsupportedOp(op)),
+    };
+    Ok(grads)
+}
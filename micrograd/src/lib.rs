@@ -1,4 +1,8 @@
 pub mod engine;
 pub mod nn;
+pub mod optim;
+pub mod pool;
+pub mod serialize;
+pub mod train;
 
 pub mod trace_graph;
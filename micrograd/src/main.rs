@@ -1,7 +1,8 @@
 use micrograd::engine::Value;
-use micrograd::nn::{MLP, Module};
+use micrograd::nn::{MLP, Module, TANH_GAIN};
 use micrograd::trace_graph::draw_dot;
 
+#[allow(dead_code)]
 fn draw_value_example() {
     // inputs x1, x2
     let x1 = Value::new(2.0);
@@ -34,14 +35,15 @@ fn draw_value_example() {
     draw_dot(&o, "./value_example.svg");
 }
 
+#[allow(dead_code)]
 fn draw_mlp_example() {
-    let xs = vec![
+    let xs = [
         vec![Value::new(2.0), Value::new(3.0), Value::new(-1.0)],
         vec![Value::new(3.0), Value::new(-1.0), Value::new(0.5)],
         vec![Value::new(0.5), Value::new(1.0), Value::new(1.0)],
         vec![Value::new(1.0), Value::new(1.0), Value::new(-1.0)],
     ];
-    let ys = vec![
+    let ys = [
         Value::new(1.0),
         Value::new(-1.0),
         Value::new(-1.0),
@@ -62,20 +64,20 @@ fn draw_mlp_example() {
 }
 
 fn train_mlp() {
-    let xs = vec![
+    let xs = [
         vec![Value::new(2.0), Value::new(3.0), Value::new(-1.0)],
         vec![Value::new(3.0), Value::new(-1.0), Value::new(0.5)],
         vec![Value::new(0.5), Value::new(1.0), Value::new(1.0)],
         vec![Value::new(1.0), Value::new(1.0), Value::new(-1.0)],
     ];
-    let ys = vec![
+    let ys = [
         Value::new(1.0),
         Value::new(-1.0),
         Value::new(-1.0),
         Value::new(1.0),
     ];
 
-    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let mlp = MLP::with_init_gain(3, vec![4, 4, 1], TANH_GAIN);
 
     let epochs = 20;
     let lr = 0.01;
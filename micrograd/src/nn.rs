@@ -1,37 +1,130 @@
-use crate::engine::Value;
+use crate::engine::{Value, logsumexp, mul_add};
 use rand::Rng;
+use rand_distr::{Distribution, Normal};
+use serde::{Deserialize, Serialize};
+use std::cell::{Cell, RefCell};
+use std::ops::RangeBounds;
+use thiserror::Error;
+
+/// Recommended initialization gain for tanh layers, see `MLP::with_init_gain`
+pub const TANH_GAIN: f64 = 5.0 / 3.0;
 
 pub trait Module {
     fn zero_grad(&self) {
         for param in self.parameters() {
-            param.set_grad(0.0);
+            param.zero_grad();
         }
     }
 
     fn parameters(&self) -> Vec<Value>;
+
+    /// Number of trainable parameters, overridden where it can be computed
+    /// without collecting the parameter vector
+    fn num_parameters(&self) -> usize {
+        self.parameters().len()
+    }
+
+    /// Switches between training (`true`) and evaluation (`false`) mode.
+    /// Container modules propagate the flag to their children.
+    fn set_training(&self, training: bool);
+
+    fn is_training(&self) -> bool;
+
+    /// Resets every parameter in place to a fresh draw of the module's
+    /// initialization. The default samples uniformly from `[-1, 1]`; modules
+    /// with another scheme override it. The parameter nodes keep their
+    /// identity, so optimizers holding them stay valid.
+    fn reinit(&self, rng: &mut impl Rng)
+    where
+        Self: Sized,
+    {
+        for param in self.parameters() {
+            param.set_data(rng.random_range(-1.0..=1.0));
+        }
+    }
+}
+
+/// How a `Neuron` draws its parameters, kept so `reinit` can repeat it
+#[derive(Debug, Clone, Copy)]
+enum Init {
+    /// Weights and bias uniform in `[-1, 1]`, see `Neuron::new`
+    Uniform,
+    /// Weights from the given normal and a zero bias, see
+    /// `Neuron::with_init_gain`
+    Kaiming(Normal<f64>),
 }
+
+impl Init {
+    fn weight(&self, rng: &mut impl Rng) -> f64 {
+        match self {
+            Init::Uniform => rng.random_range(-1.0..=1.0),
+            Init::Kaiming(normal) => normal.sample(rng),
+        }
+    }
+
+    fn bias(&self, rng: &mut impl Rng) -> f64 {
+        match self {
+            Init::Uniform => rng.random_range(-1.0..=1.0),
+            Init::Kaiming(_) => 0.0,
+        }
+    }
+
+    /// Weight standard deviation of `Kaiming`, `None` for `Uniform`
+    fn std(&self) -> Option<f64> {
+        match self {
+            Init::Uniform => None,
+            Init::Kaiming(normal) => Some(normal.std_dev()),
+        }
+    }
+}
+
 pub struct Neuron {
     w: Vec<Value>,
     b: Value,
+    training: Cell<bool>,
+    init: Init,
 }
 
 impl Neuron {
     pub fn new(nin: u32) -> Self {
+        Neuron::with_init(nin, Init::Uniform)
+    }
+
+    /// Draws weights from `N(0, gain² / nin)` and starts the bias at zero, so
+    /// with unit-variance inputs the pre-activation has a standard deviation
+    /// of about `gain` whatever the fan-in (Kaiming initialization)
+    pub fn with_init_gain(nin: u32, gain: f64) -> Self {
+        let std = gain / (nin.max(1) as f64).sqrt();
+        let normal = Normal::new(0.0, std).expect("init gain must be finite and non-negative");
+        Neuron::with_init(nin, Init::Kaiming(normal))
+    }
+
+    fn with_init(nin: u32, init: Init) -> Self {
         let mut rng = rand::rng();
         let w = (0..nin)
-            .map(|_| Value::new(rng.random_range(-1.0..=1.0)))
+            .map(|_| Value::new(init.weight(&mut rng)))
             .collect();
-        let b = Value::new(rng.random_range(-1.0..=1.0));
-        Neuron { w, b }
+        let b = Value::new(init.bias(&mut rng));
+        Neuron {
+            w,
+            b,
+            training: Cell::new(true),
+            init,
+        }
     }
 
-    pub fn forward(&self, x: &[Value]) -> Value {
-        // w * x + b
+    /// The linear combination `w · x + b`, before the nonlinearity, built
+    /// from one `mul_add` node per input
+    pub fn pre_activation(&self, x: &[Value]) -> Value {
         let mut act = self.b.clone();
         for (wi, xi) in self.w.iter().zip(x.iter()) {
-            act = act + wi.clone() * xi.clone();
+            act = mul_add(wi, xi, &act);
         }
-        act.tanh()
+        act
+    }
+
+    pub fn forward(&self, x: &[Value]) -> Value {
+        self.pre_activation(x).tanh()
     }
 }
 
@@ -41,51 +134,1009 @@ impl Module for Neuron {
         params.push(self.b.clone());
         params
     }
+
+    fn num_parameters(&self) -> usize {
+        self.w.len() + 1
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+
+    /// Redraws the parameters with the scheme the neuron was built with
+    fn reinit(&self, rng: &mut impl Rng) {
+        for w in &self.w {
+            w.set_data(self.init.weight(rng));
+        }
+        self.b.set_data(self.init.bias(rng));
+    }
 }
 
 pub struct Layer {
     neurons: Vec<Neuron>,
+    training: Cell<bool>,
 }
 
 impl Layer {
     pub fn new(nin: u32, nout: u32) -> Self {
         let neurons = (0..nout).map(|_| Neuron::new(nin)).collect();
-        Layer { neurons }
+        Layer {
+            neurons,
+            training: Cell::new(true),
+        }
+    }
+
+    /// Layer of `Neuron::with_init_gain` neurons
+    pub fn with_init_gain(nin: u32, nout: u32, gain: f64) -> Self {
+        let neurons = (0..nout)
+            .map(|_| Neuron::with_init_gain(nin, gain))
+            .collect();
+        Layer {
+            neurons,
+            training: Cell::new(true),
+        }
     }
 
     pub fn forward(&self, x: &[Value]) -> Vec<Value> {
         self.neurons.iter().map(|n| n.forward(x)).collect()
     }
+
+    pub fn neurons(&self) -> &[Neuron] {
+        &self.neurons
+    }
+
+    /// Freezes or unfreezes every weight and bias of this layer, see
+    /// `Value::set_frozen`
+    pub fn set_frozen(&self, frozen: bool) {
+        for param in self.parameters() {
+            param.set_frozen(frozen);
+        }
+    }
 }
 
 impl Module for Layer {
     fn parameters(&self) -> Vec<Value> {
         self.neurons.iter().flat_map(|n| n.parameters()).collect()
     }
+
+    fn num_parameters(&self) -> usize {
+        self.neurons.iter().map(|n| n.num_parameters()).sum()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+        for neuron in &self.neurons {
+            neuron.set_training(training);
+        }
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+
+    fn reinit(&self, rng: &mut impl Rng) {
+        for neuron in &self.neurons {
+            neuron.reinit(rng);
+        }
+    }
+}
+
+/// Randomly zeroes inputs with probability `p` during training, scaling the
+/// survivors by `1 / (1 - p)`. In evaluation mode it is a passthrough.
+pub struct Dropout {
+    p: f64,
+    training: Cell<bool>,
+}
+
+impl Dropout {
+    pub fn new(p: f64) -> Self {
+        assert!(
+            (0.0..1.0).contains(&p),
+            "Dropout probability must be in [0, 1), got {}",
+            p
+        );
+        Dropout {
+            p,
+            training: Cell::new(true),
+        }
+    }
+
+    pub fn forward(&self, x: &[Value]) -> Vec<Value> {
+        if !self.is_training() || self.p == 0.0 {
+            return x.to_vec();
+        }
+        let mut rng = rand::rng();
+        let scale = 1.0 / (1.0 - self.p);
+        x.iter()
+            .map(|xi| {
+                let mask = if rng.random::<f64>() < self.p {
+                    0.0
+                } else {
+                    scale
+                };
+                xi.clone() * Value::constant(mask)
+            })
+            .collect()
+    }
+}
+
+impl Module for Dropout {
+    fn parameters(&self) -> Vec<Value> {
+        Vec::new()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+}
+
+/// Batch normalization over a batch of feature vectors, as in the makemore
+/// batch-norm lecture.
+///
+/// In training mode each feature is normalized with the mean and (unbiased)
+/// variance of the current batch, and running estimates of both are updated
+/// as `running = (1 - momentum) * running + momentum * batch`. In evaluation
+/// mode the running estimates are used instead, so single examples can be fed.
+/// The normalized features are then scaled by `gamma` and shifted by `beta`.
+pub struct BatchNorm1d {
+    gamma: Vec<Value>,
+    beta: Vec<Value>,
+    eps: f64,
+    momentum: f64,
+    running_mean: RefCell<Vec<f64>>,
+    running_var: RefCell<Vec<f64>>,
+    training: Cell<bool>,
+}
+
+impl BatchNorm1d {
+    /// Batch norm with the usual `momentum = 0.1` and `eps = 1e-5`
+    pub fn new(dim: usize) -> Self {
+        BatchNorm1d::with_momentum(dim, 0.1, 1e-5)
+    }
+
+    pub fn with_momentum(dim: usize, momentum: f64, eps: f64) -> Self {
+        assert!(
+            (0.0..=1.0).contains(&momentum),
+            "momentum must be in [0, 1], got {momentum}"
+        );
+        BatchNorm1d {
+            gamma: (0..dim).map(|_| Value::new(1.0)).collect(),
+            beta: (0..dim).map(|_| Value::new(0.0)).collect(),
+            eps,
+            momentum,
+            running_mean: RefCell::new(vec![0.0; dim]),
+            running_var: RefCell::new(vec![1.0; dim]),
+            training: Cell::new(true),
+        }
+    }
+
+    pub fn running_mean(&self) -> Vec<f64> {
+        self.running_mean.borrow().clone()
+    }
+
+    pub fn running_var(&self) -> Vec<f64> {
+        self.running_var.borrow().clone()
+    }
+
+    /// Normalizes `batch`, one `dim`-sized feature vector per example. Training
+    /// mode needs at least two examples to estimate the variance.
+    pub fn forward(&self, batch: &[Vec<Value>]) -> Vec<Vec<Value>> {
+        let dim = self.gamma.len();
+        assert!(
+            batch.iter().all(|x| x.len() == dim),
+            "Expected every example to have {dim} features"
+        );
+
+        let (mean, var): (Vec<Value>, Vec<Value>) = if self.is_training() {
+            let n = batch.len();
+            assert!(
+                n > 1,
+                "BatchNorm1d needs at least 2 examples in training mode"
+            );
+            let stats = (0..dim)
+                .map(|j| {
+                    let column = || batch.iter().map(move |x| x[j].clone());
+                    let mean = column()
+                        .reduce(|acc, x| acc + x)
+                        .expect("batch is not empty")
+                        * Value::constant(1.0 / n as f64);
+                    let var = column()
+                        .map(|x| (x - mean.clone()).powi(2))
+                        .reduce(|acc, x| acc + x)
+                        .expect("batch is not empty")
+                        * Value::constant(1.0 / (n - 1) as f64);
+                    (mean, var)
+                })
+                .unzip();
+            self.update_running(&stats);
+            stats
+        } else {
+            (
+                self.running_mean()
+                    .into_iter()
+                    .map(Value::constant)
+                    .collect(),
+                self.running_var()
+                    .into_iter()
+                    .map(Value::constant)
+                    .collect(),
+            )
+        };
+
+        let inv_std: Vec<Value> = var
+            .into_iter()
+            .map(|v| (v + Value::constant(self.eps)).sqrt().recip())
+            .collect();
+        batch
+            .iter()
+            .map(|x| {
+                (0..dim)
+                    .map(|j| {
+                        let x_hat = (x[j].clone() - mean[j].clone()) * inv_std[j].clone();
+                        self.gamma[j].clone() * x_hat + self.beta[j].clone()
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    fn update_running(&self, (mean, var): &(Vec<Value>, Vec<Value>)) {
+        let m = self.momentum;
+        for (running, batch) in self.running_mean.borrow_mut().iter_mut().zip(mean) {
+            *running = (1.0 - m) * *running + m * batch.data();
+        }
+        for (running, batch) in self.running_var.borrow_mut().iter_mut().zip(var) {
+            *running = (1.0 - m) * *running + m * batch.data();
+        }
+    }
+}
+
+impl Module for BatchNorm1d {
+    fn parameters(&self) -> Vec<Value> {
+        self.gamma.iter().chain(&self.beta).cloned().collect()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+
+    /// Resets `gamma` to 1, `beta` to 0 and the running statistics to their
+    /// initial mean 0 and variance 1, as in `new`
+    fn reinit(&self, _rng: &mut impl Rng) {
+        for gamma in &self.gamma {
+            gamma.set_data(1.0);
+        }
+        for beta in &self.beta {
+            beta.set_data(0.0);
+        }
+        self.running_mean.borrow_mut().fill(0.0);
+        self.running_var.borrow_mut().fill(1.0);
+    }
 }
 
 pub struct MLP {
     layers: Vec<Layer>,
+    dropout: Option<Dropout>,
+    /// Adds each layer's input to its output where their sizes match
+    residual: bool,
+    training: Cell<bool>,
 }
 
 impl MLP {
     pub fn new(nin: u32, nouts: Vec<u32>) -> Self {
         let sizes: Vec<u32> = std::iter::once(nin).chain(nouts.iter().copied()).collect();
         let layers = sizes.windows(2).map(|w| Layer::new(w[0], w[1])).collect();
-        MLP { layers }
+        MLP {
+            layers,
+            dropout: None,
+            residual: false,
+            training: Cell::new(true),
+        }
+    }
+
+    /// Creates an MLP whose layers use `Layer::with_init_gain`. The uniform
+    /// `[-1, 1]` weights of `new` give pre-activations that grow with the
+    /// fan-in and saturate tanh; `TANH_GAIN` keeps them around unit scale.
+    pub fn with_init_gain(nin: u32, nouts: Vec<u32>, gain: f64) -> Self {
+        let sizes: Vec<u32> = std::iter::once(nin).chain(nouts.iter().copied()).collect();
+        MLP {
+            layers: sizes
+                .windows(2)
+                .map(|w| Layer::with_init_gain(w[0], w[1], gain))
+                .collect(),
+            ..MLP::new(nin, Vec::new())
+        }
+    }
+
+    /// Creates an MLP with residual (skip) connections: every layer whose
+    /// output has as many values as its input computes `x + layer(x)`, as in
+    /// the transformer blocks. Layers that change the size have no residual.
+    pub fn with_residual(nin: u32, nouts: Vec<u32>) -> Self {
+        MLP {
+            residual: true,
+            ..MLP::new(nin, nouts)
+        }
+    }
+
+    /// Creates an MLP that applies dropout with probability `p` after every hidden layer
+    pub fn with_dropout(nin: u32, nouts: Vec<u32>, p: f64) -> Self {
+        MLP {
+            dropout: Some(Dropout::new(p)),
+            ..MLP::new(nin, nouts)
+        }
+    }
+
+    /// Layers from input to output, e.g. to freeze or tune them individually
+    pub fn layers(&self) -> &[Layer] {
+        &self.layers
+    }
+
+    /// Freezes the layers at `range` (by index, from the input) so optimizers
+    /// skip them, e.g. to fine-tune only the last layers
+    pub fn freeze_layers(&self, range: impl RangeBounds<usize>) {
+        self.set_frozen_layers(range, true);
+    }
+
+    pub fn unfreeze_layers(&self, range: impl RangeBounds<usize>) {
+        self.set_frozen_layers(range, false);
+    }
+
+    fn set_frozen_layers(&self, range: impl RangeBounds<usize>, frozen: bool) {
+        let bounds = (range.start_bound().cloned(), range.end_bound().cloned());
+        for layer in &self.layers[bounds] {
+            layer.set_frozen(frozen);
+        }
     }
 
     pub fn forward(&self, x: &[Value]) -> Vec<Value> {
+        self.forward_with_activations(x)
+            .pop()
+            .unwrap_or_else(|| x.to_vec())
+    }
+
+    /// Mean of `loss` over the examples `xs` with targets `ys`, as a single
+    /// scalar ready for `backward`. The network must have one output.
+    pub fn batch_loss(&self, xs: &[Vec<Value>], ys: &[Value], loss: LossFn) -> Value {
+        assert_eq!(xs.len(), ys.len(), "Expected one target per example");
+        assert!(!xs.is_empty(), "batch_loss requires at least one example");
+        let total = xs
+            .iter()
+            .zip(ys)
+            .map(|(x, y)| {
+                let out = self.forward(x);
+                assert_eq!(out.len(), 1, "batch_loss expects a single output");
+                loss.apply(&out[0], y)
+            })
+            .reduce(|acc, x| acc + x)
+            .expect("batch is not empty");
+        total * Value::constant(1.0 / xs.len() as f64)
+    }
+
+    /// Forward pass returning the output of every layer, in order, so that
+    /// per-layer activations (e.g. tanh saturation) can be inspected
+    pub fn forward_with_activations(&self, x: &[Value]) -> Vec<Vec<Value>> {
+        let mut activations = Vec::with_capacity(self.layers.len());
+        let mut act = x.to_vec();
+        let last = self.layers.len().saturating_sub(1);
+        for (i, layer) in self.layers.iter().enumerate() {
+            let mut out = layer.forward(&act);
+            if i < last
+                && let Some(dropout) = &self.dropout
+            {
+                out = dropout.forward(&out);
+            }
+            if self.residual && out.len() == act.len() {
+                out = out
+                    .into_iter()
+                    .zip(&act)
+                    .map(|(o, x)| o + x.clone())
+                    .collect();
+            }
+            act = out;
+            activations.push(act.clone());
+        }
+        activations
+    }
+
+    /// Inference-only forward pass on plain numbers with every layer's weights
+    /// and biases quantized to `bits` of precision. No graph is built and
+    /// dropout is skipped.
+    ///
+    /// Quantization is symmetric per layer: values are snapped to the nearest
+    /// multiple of `max|w| / (2^(bits-1) - 1)`, so fewer bits mean coarser steps.
+    pub fn forward_quantized(&self, x: &[f64], bits: u8) -> Vec<f64> {
+        assert!(
+            (2..=32).contains(&bits),
+            "bits must be between 2 and 32, got {bits}"
+        );
+        let levels = ((1u64 << (bits - 1)) - 1) as f64;
+
         let mut act = x.to_vec();
         for layer in &self.layers {
-            act = layer.forward(&act);
+            let params: Vec<f64> = layer.parameters().iter().map(Value::data).collect();
+            let max_abs = params.iter().fold(0.0_f64, |m, p| m.max(p.abs()));
+            let scale = max_abs / levels;
+            let quantize = |w: f64| {
+                if scale > 0.0 {
+                    (w / scale).round() * scale
+                } else {
+                    w
+                }
+            };
+
+            let out: Vec<f64> = layer
+                .neurons
+                .iter()
+                .map(|n| {
+                    let dot: f64 = n
+                        .w
+                        .iter()
+                        .zip(&act)
+                        .map(|(w, x)| quantize(w.data()) * x)
+                        .sum();
+                    (quantize(n.b.data()) + dot).tanh()
+                })
+                .collect();
+            act = if self.residual && out.len() == act.len() {
+                out.iter().zip(&act).map(|(o, x)| o + x).collect()
+            } else {
+                out
+            };
         }
         act
     }
+
+    /// L2 norm of each layer's parameter gradients, in layer order, for
+    /// localizing vanishing or exploding gradients
+    pub fn layer_grad_norms(&self) -> Vec<f64> {
+        self.layers
+            .iter()
+            .map(|layer| {
+                layer
+                    .parameters()
+                    .iter()
+                    .map(|p| p.grad() * p.grad())
+                    .sum::<f64>()
+                    .sqrt()
+            })
+            .collect()
+    }
+
+    /// Update-to-data ratio of each layer after a backward pass: `lr` times the
+    /// standard deviation of the parameter gradients over the standard
+    /// deviation of the parameters themselves. Around `1e-3` is healthy; much
+    /// larger means the layer changes too fast, much smaller too slowly.
+    pub fn update_ratios(&self, lr: f64) -> Vec<f64> {
+        fn std(xs: &[f64]) -> f64 {
+            let n = xs.len() as f64;
+            let mean = xs.iter().sum::<f64>() / n;
+            (xs.iter().map(|x| (x - mean).powi(2)).sum::<f64>() / n).sqrt()
+        }
+        self.layers
+            .iter()
+            .map(|layer| {
+                let params = layer.parameters();
+                let grads: Vec<f64> = params.iter().map(Value::grad).collect();
+                let data: Vec<f64> = params.iter().map(Value::data).collect();
+                lr * std(&grads) / std(&data)
+            })
+            .collect()
+    }
+
+    /// Histogram of every parameter's data over `bins` evenly spaced buckets
+    /// spanning the smallest to the largest value. Each entry is the bucket's
+    /// lower edge and its count; the maximum lands in the last bucket.
+    pub fn weight_histogram(&self, bins: usize) -> Vec<(f64, usize)> {
+        assert!(bins > 0, "bins must be positive");
+        let data: Vec<f64> = self.parameters().iter().map(Value::data).collect();
+        if data.is_empty() {
+            return Vec::new();
+        }
+
+        let min = data.iter().copied().fold(f64::INFINITY, f64::min);
+        let max = data.iter().copied().fold(f64::NEG_INFINITY, f64::max);
+        let width = (max - min) / bins as f64;
+
+        let mut counts = vec![0; bins];
+        for x in data {
+            let bin = if width > 0.0 {
+                (((x - min) / width) as usize).min(bins - 1)
+            } else {
+                0
+            };
+            counts[bin] += 1;
+        }
+        counts
+            .into_iter()
+            .enumerate()
+            .map(|(i, count)| (min + i as f64 * width, count))
+            .collect()
+    }
+}
+
+/// Portable inference format of an [`MLP`], see [`MLP::export_json`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedMlp {
+    pub nin: usize,
+    /// Whether same-size layers add their input to their output
+    pub residual: bool,
+    pub layers: Vec<ExportedLayer>,
+}
+
+/// One layer computing `activation(weights · x + bias)`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedLayer {
+    pub nin: usize,
+    pub nout: usize,
+    pub activation: String,
+    /// `nout` rows of `nin` weights, one row per neuron, flattened row by row
+    pub weights: Vec<f64>,
+    pub bias: Vec<f64>,
+    /// Weight standard deviation of a `with_init_gain` layer, used only by
+    /// `Module::reinit`. Absent for the uniform `[-1, 1]` init of `new`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub init_std: Option<f64>,
+}
+
+#[derive(Debug, Error)]
+pub enum ImportError {
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+    #[error("Layer {layer} expects {expected} {field}, got {got}")]
+    Shape {
+        layer: usize,
+        field: &'static str,
+        expected: usize,
+        got: usize,
+    },
+    #[error("Layer {layer} has unsupported activation '{activation}'")]
+    UnsupportedActivation { layer: usize, activation: String },
+    #[error("Layer {layer} has invalid init standard deviation {std}")]
+    InvalidInitStd { layer: usize, std: f64 },
+}
+
+impl MLP {
+    /// Exports the architecture and weights as self-describing JSON that a
+    /// small interpreter in any language can run:
+    ///
+    /// ```text
+    /// {"nin": 2, "residual": false, "layers": [
+    ///   {"nin": 2, "nout": 3, "activation": "tanh", "weights": [6 numbers], "bias": [3 numbers]},
+    ///   ...
+    /// ]}
+    /// ```
+    ///
+    /// Weights are written with enough digits to be read back exactly. Layers
+    /// built with `with_init_gain` also record an `init_std`, so `reinit`
+    /// after importing draws from the same distribution. Dropout only matters
+    /// in training and is not exported.
+    pub fn export_json(&self) -> String {
+        let layers: Vec<ExportedLayer> = self
+            .layers
+            .iter()
+            .map(|layer| ExportedLayer {
+                nin: layer.neurons.first().map_or(0, |n| n.w.len()),
+                nout: layer.neurons.len(),
+                activation: "tanh".to_string(),
+                weights: layer
+                    .neurons
+                    .iter()
+                    .flat_map(|n| n.w.iter().map(Value::data))
+                    .collect(),
+                bias: layer.neurons.iter().map(|n| n.b.data()).collect(),
+                init_std: layer.neurons.first().and_then(|n| n.init.std()),
+            })
+            .collect();
+        let exported = ExportedMlp {
+            nin: layers.first().map_or(0, |l| l.nin),
+            residual: self.residual,
+            layers,
+        };
+        serde_json::to_string(&exported).expect("an exported MLP is always valid JSON")
+    }
+
+    /// Rebuilds an MLP from `export_json` output with fresh parameter nodes,
+    /// checking that every layer's shape is consistent
+    pub fn import_json(json: &str) -> Result<Self, ImportError> {
+        let exported: ExportedMlp = serde_json::from_str(json)?;
+        let mut nin = exported.nin;
+        let mut layers = Vec::with_capacity(exported.layers.len());
+        for (i, layer) in exported.layers.into_iter().enumerate() {
+            let shape = |field, expected, got| {
+                if expected == got {
+                    Ok(())
+                } else {
+                    Err(ImportError::Shape {
+                        layer: i,
+                        field,
+                        expected,
+                        got,
+                    })
+                }
+            };
+            shape("inputs", nin, layer.nin)?;
+            shape("weights", layer.nin * layer.nout, layer.weights.len())?;
+            shape("biases", layer.nout, layer.bias.len())?;
+            if layer.activation != "tanh" {
+                return Err(ImportError::UnsupportedActivation {
+                    layer: i,
+                    activation: layer.activation,
+                });
+            }
+            let init = match layer.init_std {
+                None => Init::Uniform,
+                Some(std) => Init::Kaiming(
+                    Normal::new(0.0, std)
+                        .map_err(|_| ImportError::InvalidInitStd { layer: i, std })?,
+                ),
+            };
+
+            let neurons = layer
+                .bias
+                .iter()
+                .enumerate()
+                .map(|(j, &b)| Neuron {
+                    w: layer.weights[j * layer.nin..(j + 1) * layer.nin]
+                        .iter()
+                        .map(|&w| Value::new(w))
+                        .collect(),
+                    b: Value::new(b),
+                    training: Cell::new(true),
+                    init,
+                })
+                .collect();
+            layers.push(Layer {
+                neurons,
+                training: Cell::new(true),
+            });
+            nin = layer.nout;
+        }
+        Ok(MLP {
+            layers,
+            dropout: None,
+            residual: exported.residual,
+            training: Cell::new(true),
+        })
+    }
 }
 
 impl Module for MLP {
     fn parameters(&self) -> Vec<Value> {
         self.layers.iter().flat_map(|l| l.parameters()).collect()
     }
+
+    fn num_parameters(&self) -> usize {
+        self.layers.iter().map(|l| l.num_parameters()).sum()
+    }
+
+    fn set_training(&self, training: bool) {
+        self.training.set(training);
+        for layer in &self.layers {
+            layer.set_training(training);
+        }
+        if let Some(dropout) = &self.dropout {
+            dropout.set_training(training);
+        }
+    }
+
+    fn is_training(&self) -> bool {
+        self.training.get()
+    }
+
+    fn reinit(&self, rng: &mut impl Rng) {
+        for layer in &self.layers {
+            layer.reinit(rng);
+        }
+    }
+}
+
+impl<'a> IntoIterator for &'a MLP {
+    type Item = &'a Layer;
+    type IntoIter = std::slice::Iter<'a, Layer>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.layers.iter()
+    }
+}
+
+/// Resets the gradients of input `Value`s that are reused across iterations.
+///
+/// `Module::zero_grad` only covers parameters. Activations are rebuilt on
+/// every forward pass, but inputs created once and fed again keep accumulating
+/// gradient from every `backward`. The per-iteration pattern is:
+///
+/// ```text
+/// model.zero_grad();
+/// zero_grad_inputs(&xs);
+/// let loss = ...forward...;
+/// loss.backward();
+/// ```
+pub fn zero_grad_inputs(inputs: &[Value]) {
+    for input in inputs {
+        input.zero_grad();
+    }
+}
+
+/// Element-wise `xs[i] + bias[i]`
+pub fn add_bias(xs: &[Value], bias: &[Value]) -> Vec<Value> {
+    assert_eq!(
+        xs.len(),
+        bias.len(),
+        "Inputs and bias must have the same length"
+    );
+    xs.iter()
+        .zip(bias)
+        .map(|(x, b)| x.clone() + b.clone())
+        .collect()
+}
+
+/// Adds the same `bias` vector to every example of `batch`. The bias nodes
+/// are shared, so each one's gradient accumulates over the whole batch.
+pub fn add_bias_batch(batch: &[Vec<Value>], bias: &[Value]) -> Vec<Vec<Value>> {
+    batch.iter().map(|xs| add_bias(xs, bias)).collect()
+}
+
+/// Adds the same `bias` to every element. The bias node is shared, so its
+/// gradient accumulates the contributions of all elements.
+pub fn add_scalar_bias(xs: &[Value], bias: &Value) -> Vec<Value> {
+    xs.iter().map(|x| x.clone() + bias.clone()).collect()
+}
+
+/// Per-layer `(mean, std)` of activation data, e.g. from `MLP::forward_with_activations`.
+/// The standard deviation is the population one (divides by `n`).
+pub fn activation_stats(activations: &[Vec<Value>]) -> Vec<(f64, f64)> {
+    activations
+        .iter()
+        .map(|layer| {
+            let n = layer.len() as f64;
+            let mean = layer.iter().map(Value::data).sum::<f64>() / n;
+            let var = layer
+                .iter()
+                .map(|v| (v.data() - mean).powi(2))
+                .sum::<f64>()
+                / n;
+            (mean, var.sqrt())
+        })
+        .collect()
+}
+
+/// How a loss helper combines its per-example losses, like PyTorch's
+/// `reduction` argument
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reduction {
+    /// A single value, the average over examples
+    Mean,
+    /// A single value, the total over examples
+    Sum,
+    /// One loss per example, e.g. to weight them individually
+    None,
+}
+
+impl Reduction {
+    /// Combines `losses` into a single mean or sum, or returns them unchanged
+    /// for `Reduction::None`. Panics on an empty `losses` unless `None`.
+    pub fn reduce(self, losses: Vec<Value>) -> Vec<Value> {
+        if self == Reduction::None {
+            return losses;
+        }
+        let n = losses.len() as f64;
+        let total = losses
+            .into_iter()
+            .reduce(|acc, x| acc + x)
+            .expect("cannot reduce an empty set of losses");
+        match self {
+            Reduction::Mean => vec![total * Value::constant(1.0 / n)],
+            _ => vec![total],
+        }
+    }
+}
+
+/// Mean squared error between predictions and their targets
+pub fn mse_loss(preds: &[Value], targets: &[Value]) -> Value {
+    assert!(
+        !preds.is_empty(),
+        "mse_loss requires at least one prediction"
+    );
+    single(mse_loss_with(preds, targets, Reduction::Mean))
+}
+
+/// Squared errors `(target - pred)^2` combined according to `reduction`
+pub fn mse_loss_with(preds: &[Value], targets: &[Value], reduction: Reduction) -> Vec<Value> {
+    assert_eq!(
+        preds.len(),
+        targets.len(),
+        "Expected as many predictions as targets"
+    );
+    let losses = preds
+        .iter()
+        .zip(targets.iter())
+        .map(|(y_hat, y)| (y.clone() - y_hat.clone()).powi(2))
+        .collect();
+    reduction.reduce(losses)
+}
+
+/// Mean cross-entropy of a batch of classifier logits against the target
+/// class of each example: `logsumexp(logits[i]) - logits[i][targets[i]]`,
+/// i.e. the negative log-likelihood of the softmax. Panics on a target index
+/// outside its logits.
+pub fn batch_cross_entropy(logits: &[Vec<Value>], targets: &[usize]) -> Value {
+    assert!(
+        !logits.is_empty(),
+        "batch_cross_entropy requires at least one example"
+    );
+    single(batch_cross_entropy_with(logits, targets, Reduction::Mean))
+}
+
+/// Per-example cross-entropies of `batch_cross_entropy` combined according
+/// to `reduction`
+pub fn batch_cross_entropy_with(
+    logits: &[Vec<Value>],
+    targets: &[usize],
+    reduction: Reduction,
+) -> Vec<Value> {
+    assert_eq!(
+        logits.len(),
+        targets.len(),
+        "Expected one target per example"
+    );
+    let losses = logits
+        .iter()
+        .zip(targets)
+        .map(|(row, &target)| {
+            assert!(
+                target < row.len(),
+                "target {} out of range for {} logits",
+                target,
+                row.len()
+            );
+            logsumexp(row) - row[target].clone()
+        })
+        .collect();
+    reduction.reduce(losses)
+}
+
+/// The only loss left by a `Mean` or `Sum` reduction
+fn single(mut losses: Vec<Value>) -> Value {
+    losses.pop().expect("a reduced loss has exactly one value")
+}
+
+/// Per-example loss averaged by `MLP::batch_loss`
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LossFn {
+    /// Squared error `(target - pred)^2`, averaging to `mse_loss`
+    Mse,
+    /// `huber_loss` with the given delta
+    Huber(f64),
+}
+
+impl LossFn {
+    pub fn apply(&self, pred: &Value, target: &Value) -> Value {
+        match *self {
+            LossFn::Mse => (target.clone() - pred.clone()).powi(2),
+            LossFn::Huber(delta) => huber_loss(pred, target, delta),
+        }
+    }
+}
+
+/// Added to `q` inside the log of `kl_divergence` so zero entries stay finite
+const KL_EPS: f64 = 1e-10;
+
+/// KL divergence `sum p * (log p - log q)` between probability vectors, e.g.
+/// softmax outputs. Zero entries of `q` are offset by a small epsilon, and
+/// terms with `p == 0` contribute nothing (`0 * log 0 = 0`).
+pub fn kl_divergence(p: &[Value], q: &[Value]) -> Value {
+    assert_eq!(p.len(), q.len(), "Expected distributions of equal length");
+    p.iter()
+        .zip(q.iter())
+        .filter(|(pi, _)| pi.data() != 0.0)
+        .map(|(pi, qi)| {
+            let log_q = (qi.clone() + Value::constant(KL_EPS)).log();
+            pi.clone() * (pi.log() - log_q)
+        })
+        .reduce(|acc, x| acc + x)
+        .unwrap_or_else(|| Value::new(0.0))
+}
+
+/// Huber (smooth L1) loss: `0.5 * e^2` for `|e| <= delta` and
+/// `delta * (|e| - 0.5 * delta)` beyond, with `e = pred - target`.
+///
+/// Both pieces meet with the same value and slope at `|e| == delta`, which
+/// takes the quadratic branch.
+pub fn huber_loss(pred: &Value, target: &Value, delta: f64) -> Value {
+    assert!(delta > 0.0, "delta must be positive, got {delta}");
+    let error = pred.clone() - target.clone();
+    if error.data().abs() <= delta {
+        Value::constant(0.5) * error.powi(2)
+    } else {
+        Value::constant(delta) * (error.abs() - Value::constant(0.5 * delta))
+    }
+}
+
+/// `huber_loss` of each prediction against its target, combined according to
+/// `reduction`
+pub fn huber_loss_with(
+    preds: &[Value],
+    targets: &[Value],
+    delta: f64,
+    reduction: Reduction,
+) -> Vec<Value> {
+    assert_eq!(
+        preds.len(),
+        targets.len(),
+        "Expected as many predictions as targets"
+    );
+    let losses = preds
+        .iter()
+        .zip(targets)
+        .map(|(pred, target)| huber_loss(pred, target, delta))
+        .collect();
+    reduction.reduce(losses)
+}
+
+/// Attention over `Value` matrices, `softmax(q k^T / sqrt(d)) v`, with one
+/// row per position: `q` is `[n_q][d]`, `k` is `[n_k][d]` and `v` is
+/// `[n_k][d_v]`, giving `[n_q][d_v]`. Every position may attend to every
+/// other (no causal mask). The softmax is `exp(s - logsumexp(s))`, so the
+/// whole computation stays differentiable through the engine. Panics without
+/// keys, on zero-width keys or on mismatched shapes.
+pub fn scaled_dot_product_attention(
+    q: &[Vec<Value>],
+    k: &[Vec<Value>],
+    v: &[Vec<Value>],
+) -> Vec<Vec<Value>> {
+    assert!(!k.is_empty(), "attention needs at least one key");
+    assert_eq!(k.len(), v.len(), "Expected one value row per key row");
+    let d = k[0].len();
+    assert!(d > 0, "attention needs keys of at least one dimension");
+    assert!(
+        q.iter().chain(k).all(|row| row.len() == d),
+        "Expected all query and key rows to have {} columns",
+        d
+    );
+    let d_v = v.first().map_or(0, Vec::len);
+    let scale = Value::constant(1.0 / (d as f64).sqrt());
+
+    q.iter()
+        .map(|qi| {
+            let scores: Vec<Value> = k
+                .iter()
+                .map(|kj| {
+                    let dot = qi
+                        .iter()
+                        .zip(kj)
+                        .map(|(a, b)| a.clone() * b.clone())
+                        .reduce(|acc, x| acc + x)
+                        .unwrap_or_else(|| Value::constant(0.0));
+                    dot * scale.clone()
+                })
+                .collect();
+            let lse = logsumexp(&scores);
+            let weights: Vec<Value> = scores
+                .into_iter()
+                .map(|s| (s - lse.clone()).exp())
+                .collect();
+            (0..d_v)
+                .map(|c| {
+                    weights
+                        .iter()
+                        .zip(v)
+                        .map(|(w, vj)| w.clone() * vj[c].clone())
+                        .reduce(|acc, x| acc + x)
+                        .expect("keys are not empty")
+                })
+                .collect()
+        })
+        .collect()
 }
@@ -0,0 +1,241 @@
+use crate::engine::Value;
+
+/// Update rule applied to a fixed set of parameters after each backward pass
+pub trait Optimizer {
+    /// Updates every parameter from its current gradient, skipping frozen ones
+    /// (see `Value::set_frozen`)
+    fn step(&mut self);
+
+    /// Resets the gradient of every parameter to zero
+    fn zero_grad(&self);
+
+    fn lr(&self) -> f64;
+
+    /// Sets the base learning rate. Groups added with `add_group` keep their own.
+    fn set_lr(&mut self, lr: f64);
+
+    /// Adds `params` as a parameter group updated with its own learning rate
+    /// `lr` instead of the base one, e.g. a lower rate for early layers
+    fn add_group(&mut self, params: Vec<Value>, lr: f64);
+
+    /// Clears any internal state (e.g. moment estimates) so the optimizer
+    /// behaves as if freshly constructed
+    fn reset(&mut self) {}
+}
+
+/// Divides every gradient by `scale`, undoing `Value::backward_scaled`
+pub fn unscale_grads(params: &[Value], scale: f64) {
+    for p in params {
+        let grad = p.grad() / scale;
+        p.zero_grad();
+        p.set_grad(grad);
+    }
+}
+
+fn zero_grads(params: &[Value]) {
+    for p in params {
+        p.zero_grad();
+    }
+}
+
+/// Learning rate for a parameter whose group rate is `group_lr`
+fn effective_lr(base: f64, group_lr: Option<f64>) -> f64 {
+    group_lr.unwrap_or(base)
+}
+
+// ============================================================================
+// SGD
+// ============================================================================
+
+/// Plain stochastic gradient descent: `p -= lr * grad`
+pub struct SGD {
+    params: Vec<Value>,
+    /// Learning rate of each parameter's group, `None` for the base rate
+    group_lr: Vec<Option<f64>>,
+    lr: f64,
+}
+
+impl SGD {
+    pub fn new(params: Vec<Value>, lr: f64) -> Self {
+        let n = params.len();
+        SGD {
+            params,
+            group_lr: vec![None; n],
+            lr,
+        }
+    }
+}
+
+impl Optimizer for SGD {
+    fn step(&mut self) {
+        for (p, &group_lr) in self.params.iter().zip(&self.group_lr) {
+            if p.is_frozen() {
+                continue;
+            }
+            p.set_data(p.data() - effective_lr(self.lr, group_lr) * p.grad());
+        }
+    }
+
+    fn zero_grad(&self) {
+        zero_grads(&self.params);
+    }
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn add_group(&mut self, params: Vec<Value>, lr: f64) {
+        self.group_lr.extend(std::iter::repeat_n(Some(lr), params.len()));
+        self.params.extend(params);
+    }
+}
+
+// ============================================================================
+// Adam
+// ============================================================================
+
+/// Adam with bias-corrected first and second moment estimates
+pub struct Adam {
+    params: Vec<Value>,
+    /// Learning rate of each parameter's group, `None` for the base rate
+    group_lr: Vec<Option<f64>>,
+    lr: f64,
+    beta1: f64,
+    beta2: f64,
+    eps: f64,
+    m: Vec<f64>,
+    v: Vec<f64>,
+    t: i32,
+}
+
+impl Adam {
+    /// Adam with the usual defaults `beta1 = 0.9`, `beta2 = 0.999`, `eps = 1e-8`
+    pub fn new(params: Vec<Value>, lr: f64) -> Self {
+        Adam::with_betas(params, lr, 0.9, 0.999, 1e-8)
+    }
+
+    pub fn with_betas(params: Vec<Value>, lr: f64, beta1: f64, beta2: f64, eps: f64) -> Self {
+        let n = params.len();
+        Adam {
+            params,
+            group_lr: vec![None; n],
+            lr,
+            beta1,
+            beta2,
+            eps,
+            m: vec![0.0; n],
+            v: vec![0.0; n],
+            t: 0,
+        }
+    }
+
+    /// Bias-corrected update direction for parameter `i`, updating its moments
+    fn update(&mut self, i: usize, grad: f64) -> f64 {
+        self.m[i] = self.beta1 * self.m[i] + (1.0 - self.beta1) * grad;
+        self.v[i] = self.beta2 * self.v[i] + (1.0 - self.beta2) * grad * grad;
+        let m_hat = self.m[i] / (1.0 - self.beta1.powi(self.t));
+        let v_hat = self.v[i] / (1.0 - self.beta2.powi(self.t));
+        m_hat / (v_hat.sqrt() + self.eps)
+    }
+}
+
+impl Optimizer for Adam {
+    fn step(&mut self) {
+        self.t += 1;
+        for i in 0..self.params.len() {
+            let p = self.params[i].clone();
+            if p.is_frozen() {
+                continue;
+            }
+            let update = self.update(i, p.grad());
+            p.set_data(p.data() - effective_lr(self.lr, self.group_lr[i]) * update);
+        }
+    }
+
+    fn zero_grad(&self) {
+        zero_grads(&self.params);
+    }
+
+    fn lr(&self) -> f64 {
+        self.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.lr = lr;
+    }
+
+    fn add_group(&mut self, params: Vec<Value>, lr: f64) {
+        let n = params.len();
+        self.group_lr.extend(std::iter::repeat_n(Some(lr), n));
+        self.m.extend(std::iter::repeat_n(0.0, n));
+        self.v.extend(std::iter::repeat_n(0.0, n));
+        self.params.extend(params);
+    }
+
+    fn reset(&mut self) {
+        self.m.fill(0.0);
+        self.v.fill(0.0);
+        self.t = 0;
+    }
+}
+
+// ============================================================================
+// AdamW
+// ============================================================================
+
+/// Adam with decoupled weight decay: parameters shrink by `lr * weight_decay`
+/// each step independently of the gradient-based update
+pub struct AdamW {
+    adam: Adam,
+    weight_decay: f64,
+}
+
+impl AdamW {
+    pub fn new(params: Vec<Value>, lr: f64, weight_decay: f64) -> Self {
+        AdamW {
+            adam: Adam::new(params, lr),
+            weight_decay,
+        }
+    }
+}
+
+impl Optimizer for AdamW {
+    fn step(&mut self) {
+        let adam = &mut self.adam;
+        adam.t += 1;
+        for i in 0..adam.params.len() {
+            let p = adam.params[i].clone();
+            if p.is_frozen() {
+                continue;
+            }
+            let update = adam.update(i, p.grad());
+            let lr = effective_lr(adam.lr, adam.group_lr[i]);
+            let decayed = p.data() * (1.0 - lr * self.weight_decay);
+            p.set_data(decayed - lr * update);
+        }
+    }
+
+    fn zero_grad(&self) {
+        self.adam.zero_grad();
+    }
+
+    fn lr(&self) -> f64 {
+        self.adam.lr
+    }
+
+    fn set_lr(&mut self, lr: f64) {
+        self.adam.set_lr(lr);
+    }
+
+    fn add_group(&mut self, params: Vec<Value>, lr: f64) {
+        self.adam.add_group(params, lr);
+    }
+
+    fn reset(&mut self) {
+        self.adam.reset();
+    }
+}
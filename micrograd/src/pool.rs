@@ -0,0 +1,70 @@
+use thiserror::Error;
+
+use crate::engine::Value;
+
+#[derive(Debug, Error)]
+pub enum PoolError {
+    #[error("Op '{0}' cannot be recomputed in place")]
+    UnsupportedOp(&'static str),
+}
+
+/// Reuses the nodes of one forward graph across the iterations of a training
+/// loop whose graph structure never changes.
+///
+/// Building a graph allocates every node: an `Rc`, a `RefCell`, the `prev`
+/// list and the backward closure. The pool keeps the nodes of a graph built
+/// once, and `refresh` recomputes the data of each op node from its inputs in
+/// place and zeroes every gradient, so later iterations do not allocate the
+/// graph's nodes and closures again. `backward` itself still allocates its
+/// working state (the topological order and the visited and saved-gradient
+/// tables) on every call:
+///
+/// ```text
+/// let pool = GraphPool::new(&mlp.batch_loss(&xs, &ys, LossFn::Mse))?;
+/// for _ in 0..epochs {
+///     pool.refresh(); // forward pass with the current parameters
+///     pool.root().backward();
+///     optimizer.step();
+/// }
+/// ```
+///
+/// Leaves keep their data, so parameters updated by an optimizer and inputs
+/// changed with `set_data` are picked up. Constants created while building the
+/// graph, such as dropout masks or the batch statistics of `BatchNorm1d`, are
+/// reused as they were.
+pub struct GraphPool {
+    /// Nodes of the graph, each after all of its inputs, root last
+    nodes: Vec<Value>,
+}
+
+impl GraphPool {
+    /// Takes over the graph rooted at `root`. Fails on ops that cannot be
+    /// recomputed, i.e. `"checkpoint"`.
+    pub fn new(root: &Value) -> Result<Self, PoolError> {
+        let nodes = root.topo_order();
+        for v in &nodes {
+            if !v.recompute() {
+                return Err(PoolError::UnsupportedOp(v.op().unwrap_or_default()));
+            }
+        }
+        Ok(GraphPool { nodes })
+    }
+
+    pub fn root(&self) -> &Value {
+        self.nodes.last().expect("a graph has at least its root")
+    }
+
+    /// Number of nodes reused on every refresh
+    pub fn num_nodes(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Reruns the forward pass in place and zeroes every gradient, leaving the
+    /// graph as if it had just been built from the current leaf data
+    pub fn refresh(&self) {
+        for v in &self.nodes {
+            v.recompute();
+            v.zero_grad();
+        }
+    }
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::engine::{NodeKind, Value, mul_add};
+
+#[derive(Debug, Error)]
+pub enum GraphError {
+    #[error("Op '{0}' cannot be serialized")]
+    UnsupportedOp(String),
+    #[error("Op '{op}' expects {expected} inputs, got {got}")]
+    WrongArity {
+        op: String,
+        expected: usize,
+        got: usize,
+    },
+    #[error("Op '{0}' is missing its argument")]
+    MissingArg(String),
+    #[error("Op 'recip' has a zero input")]
+    RecipOfZero,
+    #[error("Node {node} refers to input {input}, which does not precede it")]
+    InvalidInput { node: usize, input: usize },
+    #[error("Graph has no nodes")]
+    Empty,
+    #[error(transparent)]
+    Json(#[from] serde_json::Error),
+}
+
+/// How a leaf is rebuilt, mirroring the leaf variants of `NodeKind`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LeafKind {
+    /// `Value::new`, receiving gradients
+    #[default]
+    Param,
+    /// `Value::new(..).as_input()`
+    Input,
+    /// `Value::constant`, never receiving gradients
+    Const,
+}
+
+/// One node of a serialized graph. `prev` holds the indices of its inputs,
+/// which always come earlier in the node list.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedNode {
+    pub data: f64,
+    pub op: Option<String>,
+    pub op_arg: Option<f64>,
+    pub label: Option<String>,
+    pub prev: Vec<usize>,
+    /// Kind of a leaf; `None` for op nodes. Missing in older files, where
+    /// every leaf was a param.
+    #[serde(default)]
+    pub leaf: Option<LeafKind>,
+    /// Whether optimizers skip this node, see `Value::set_frozen`
+    #[serde(default)]
+    pub frozen: bool,
+}
+
+/// A whole computation graph in topological order, with the root last
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct SerializedGraph {
+    pub nodes: Vec<SerializedNode>,
+}
+
+impl SerializedGraph {
+    /// Captures the structure, data, labels, leaf kinds and frozen flags of
+    /// the graph rooted at `root`. Gradients are not stored. Fails on ops
+    /// that cannot be rebuilt by name, such as `checkpoint`.
+    pub fn from_value(root: &Value) -> Result<Self, GraphError> {
+        let topo = root.topo_order();
+        let index: HashMap<*const (), usize> =
+            topo.iter().enumerate().map(|(i, v)| (v.ptr(), i)).collect();
+
+        let nodes = topo
+            .iter()
+            .map(|v| {
+                if let Some(op) = v.op() {
+                    arity(op)?;
+                }
+                Ok(SerializedNode {
+                    data: v.data(),
+                    op: v.op().map(str::to_string),
+                    op_arg: v.op_arg(),
+                    label: v.label(),
+                    prev: v.prev().iter().map(|p| index[&p.ptr()]).collect(),
+                    leaf: match v.kind() {
+                        NodeKind::Op => None,
+                        NodeKind::Param => Some(LeafKind::Param),
+                        NodeKind::Input => Some(LeafKind::Input),
+                        NodeKind::Const => Some(LeafKind::Const),
+                    },
+                    frozen: v.is_frozen(),
+                })
+            })
+            .collect::<Result<_, GraphError>>()?;
+        Ok(SerializedGraph { nodes })
+    }
+
+    /// Rebuilds the graph through the regular ops, so every node gets its
+    /// backward closure back, and returns the root. Leaves take their stored
+    /// data and kind; every other node is recomputed from its inputs.
+    pub fn to_value(&self) -> Result<Value, GraphError> {
+        let mut values: Vec<Value> = Vec::with_capacity(self.nodes.len());
+        for (i, node) in self.nodes.iter().enumerate() {
+            let inputs = node
+                .prev
+                .iter()
+                .map(|&p| {
+                    values
+                        .get(p)
+                        .cloned()
+                        .ok_or(GraphError::InvalidInput { node: i, input: p })
+                })
+                .collect::<Result<Vec<_>, _>>()?;
+
+            let value = match &node.op {
+                None => match node.leaf.unwrap_or_default() {
+                    LeafKind::Param => Value::new(node.data),
+                    LeafKind::Input => Value::new(node.data).as_input(),
+                    LeafKind::Const => Value::constant(node.data),
+                },
+                Some(op) => apply(op, node.op_arg, &inputs)?,
+            };
+            value.set_frozen(node.frozen);
+            if let Some(label) = &node.label {
+                value.set_label(label.clone());
+            }
+            values.push(value);
+        }
+        values.pop().ok_or(GraphError::Empty)
+    }
+}
+
+/// Serializes the graph rooted at `root` to JSON
+pub fn to_json(root: &Value) -> Result<String, GraphError> {
+    Ok(serde_json::to_string(&SerializedGraph::from_value(root)?)?)
+}
+
+/// Rebuilds a differentiable graph from `to_json` output, returning its root
+pub fn from_json(json: &str) -> Result<Value, GraphError> {
+    serde_json::from_str::<SerializedGraph>(json)?.to_value()
+}
+
+/// Number of inputs each serializable op takes
+fn arity(op: &str) -> Result<usize, GraphError> {
+    match op {
+        "mul_add" => Ok(3),
+        "+" | "*" | "-" => Ok(2),
+        "neg" | "abs" | "sqrt" | "tanh" | "relu6" | "powi" | "log" | "exp" | "exp_clamped"
+        | "recip" => Ok(1),
+        _ => Err(GraphError::UnsupportedOp(op.to_string())),
+    }
+}
+
+fn apply(op: &str, arg: Option<f64>, inputs: &[Value]) -> Result<Value, GraphError> {
+    let expected = arity(op)?;
+    if inputs.len() != expected {
+        return Err(GraphError::WrongArity {
+            op: op.to_string(),
+            expected,
+            got: inputs.len(),
+        });
+    }
+    let arg = || arg.ok_or_else(|| GraphError::MissingArg(op.to_string()));
+
+    let x = inputs[0].clone();
+    Ok(match op {
+        "+" => x + inputs[1].clone(),
+        "*" => x * inputs[1].clone(),
+        "mul_add" => mul_add(&x, &inputs[1], &inputs[2]),
+        "-" => x - inputs[1].clone(),
+        "neg" => -x,
+        "abs" => x.abs(),
+        "sqrt" => x.sqrt(),
+        "tanh" => x.tanh(),
+        "relu6" => x.relu6(),
+        "powi" => x.powi(arg()? as i32),
+        "log" => x.log(),
+        "exp" => x.exp(),
+        "exp_clamped" => x.exp_clamped(arg()?),
+        "recip" if x.data() == 0.0 => return Err(GraphError::RecipOfZero),
+        "recip" => x.recip(),
+        _ => unreachable!("arity accepted an unknown op"),
+    })
+}
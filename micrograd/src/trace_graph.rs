@@ -1,23 +1,21 @@
-use crate::engine::Value;
+use crate::engine::{NodeKind, Value};
 use std::collections::HashMap;
 
+/// An edge from a child node ID to its parent node ID, tagged with the parent's op
+type Edge = (usize, usize, Option<&'static str>);
+
 /// Trace the computation graph and return node ID map and edges
-fn trace(
-    root: &Value,
-) -> (
-    HashMap<usize, Value>,
-    Vec<(usize, usize, Option<&'static str>)>,
-) {
+fn trace(root: &Value) -> (HashMap<usize, Value>, Vec<Edge>) {
     let mut seen: HashMap<*const (), usize> = HashMap::new();
     let mut nodes: HashMap<usize, Value> = HashMap::new();
-    let mut edges: Vec<(usize, usize, Option<&'static str>)> = Vec::new();
+    let mut edges: Vec<Edge> = Vec::new();
     let mut next_id: usize = 0;
 
     fn build(
         v: &Value,
         seen: &mut HashMap<*const (), usize>,
         nodes: &mut HashMap<usize, Value>,
-        edges: &mut Vec<(usize, usize, Option<&'static str>)>,
+        edges: &mut Vec<Edge>,
         next_id: &mut usize,
     ) -> usize {
         let ptr = v.ptr();
@@ -43,26 +41,74 @@ fn trace(
     (nodes, edges)
 }
 
-pub fn draw_dot(root: &Value, output_path: &str) {
+/// Direction in which graphviz lays out the graph ranks
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum RankDir {
+    /// Left to right
+    #[default]
+    LR,
+    /// Top to bottom
+    TB,
+    /// Right to left
+    RL,
+    /// Bottom to top
+    BT,
+}
+
+impl RankDir {
+    fn as_str(self) -> &'static str {
+        match self {
+            RankDir::LR => "LR",
+            RankDir::TB => "TB",
+            RankDir::RL => "RL",
+            RankDir::BT => "BT",
+        }
+    }
+}
+
+/// DOT attributes distinguishing the kinds of node: inputs are filled blue
+/// boxes, parameters rounded yellow boxes and constants dashed boxes
+fn node_style(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Op => "shape=record",
+        NodeKind::Input => "shape=record, style=filled, fillcolor=lightblue",
+        NodeKind::Param => "shape=Mrecord, style=filled, fillcolor=lightyellow",
+        NodeKind::Const => "shape=record, style=dashed",
+    }
+}
+
+/// Options controlling the DOT rendering of the trace graph
+#[derive(Debug, Clone, Default)]
+pub struct DotOptions {
+    /// Label every edge with the gradient flowing back along it into the input node
+    pub show_grad_flow: bool,
+    /// Layout direction, left to right by default
+    pub rankdir: RankDir,
+}
+
+/// Render the computation graph rooted at `root` as a DOT string
+pub fn to_dot(root: &Value, options: &DotOptions) -> String {
     let (nodes, edges) = trace(root);
 
     let mut dot_string = String::new();
     dot_string.push_str("digraph trace_graph {\n");
-    dot_string.push_str("rankdir=LR;\n");
+    dot_string.push_str(&format!("rankdir={};\n", options.rankdir.as_str()));
 
     // Add all value nodes
     for (id, val) in &nodes {
-        let name = match val.label() {
-            Some(name) => name,
-            None => String::new(),
-        };
+        let name = val.label().unwrap_or_default();
         let label = format!(
             "{{ {} | data {:.4} | grad {:.4} }}",
             name,
             val.data(),
             val.grad()
         );
-        dot_string.push_str(&format!("n{} [label=\"{}\", shape=record];\n", id, label));
+        dot_string.push_str(&format!(
+            "n{} [label=\"{}\", {}];\n",
+            id,
+            label,
+            node_style(val.kind())
+        ));
 
         if val.op().is_some() {
             dot_string.push_str(&format!(
@@ -74,16 +120,367 @@ pub fn draw_dot(root: &Value, output_path: &str) {
         }
     }
 
+    // Gradient contributions per node, indexed by input position. A node's
+    // edges are traced in `prev` order, so counting them recovers the position.
+    let mut flows: HashMap<usize, Vec<f64>> = HashMap::new();
+    let mut positions: HashMap<usize, usize> = HashMap::new();
+
     // Draw edges from input nodes to op nodes
     for (from, to, op) in &edges {
+        let attrs = if options.show_grad_flow {
+            let flow = flows
+                .entry(*to)
+                .or_insert_with(|| nodes[to].grad_contributions());
+            let position = positions.entry(*to).or_insert(0);
+            let attrs = format!(" [label=\"{:.4}\"]", flow[*position]);
+            *position += 1;
+            attrs
+        } else {
+            String::new()
+        };
+
         if op.is_some() {
-            dot_string.push_str(&format!("n{} -> op{};\n", from, to)); // input to op
+            dot_string.push_str(&format!("n{} -> op{}{};\n", from, to, attrs)); // input to op
         } else {
-            dot_string.push_str(&format!("n{} -> n{};\n", from, to)); // raw edge (no op)
+            dot_string.push_str(&format!("n{} -> n{}{};\n", from, to, attrs)); // raw edge (no op)
         }
     }
 
     dot_string.push_str("}\n");
+    dot_string
+}
+
+/// Label and op of a node, e.g. `n (tanh)`
+fn describe(v: &Value) -> String {
+    let name = v.label().unwrap_or_default();
+    match v.op() {
+        Some(op) if name.is_empty() => op.to_string(),
+        Some(op) => format!("{} ({})", name, op),
+        None => name,
+    }
+}
+
+/// Render the computation graph rooted at `root` as an indented text tree,
+/// one node per line with its op, data and grad.
+///
+/// Nodes are numbered in the same order as the `n{id}` nodes of `to_dot`. A
+/// node reachable along several paths is expanded the first time only, and
+/// later occurrences refer back to it by number.
+pub fn print_tree(root: &Value) -> String {
+    fn build(v: &Value, depth: usize, seen: &mut HashMap<*const (), usize>, out: &mut String) {
+        let indent = "  ".repeat(depth);
+        if let Some(&id) = seen.get(&v.ptr()) {
+            out.push_str(&format!("{}#{} {} (see above)\n", indent, id, describe(v)));
+            return;
+        }
+
+        let id = seen.len();
+        seen.insert(v.ptr(), id);
+        out.push_str(&format!(
+            "{}#{} {} | data {:.4} | grad {:.4}\n",
+            indent,
+            id,
+            describe(v),
+            v.data(),
+            v.grad()
+        ));
+        for child in v.prev() {
+            build(&child, depth + 1, seen, out);
+        }
+    }
+
+    let mut out = String::new();
+    build(root, 0, &mut HashMap::new(), &mut out);
+    out
+}
+
+/// Node list and boolean adjacency matrix of the graph rooted at `root`.
+///
+/// Nodes are ordered as in `to_dot` and `print_tree`, root first, and
+/// `matrix[i][j]` is true when node `j` is an input (`prev`) of node `i`,
+/// i.e. the edges point from parent to child, away from the root.
+pub fn adjacency(root: &Value) -> (Vec<Value>, Vec<Vec<bool>>) {
+    let (nodes, edges) = trace(root);
+    let n = nodes.len();
+    let mut matrix = vec![vec![false; n]; n];
+    for (child, parent, _) in edges {
+        matrix[parent][child] = true;
+    }
+    let nodes = (0..n).map(|id| nodes[&id].clone()).collect();
+    (nodes, matrix)
+}
+
+/// Number of nodes produced by each op (`"+"`, `"*"`, `"tanh"`, ...) in the
+/// graph rooted at `root`, a rough measure of the cost of a forward pass.
+/// Leaves have no op and are not counted, and shared nodes are counted once.
+pub fn op_counts(root: &Value) -> HashMap<&'static str, usize> {
+    let (nodes, _) = trace(root);
+    let mut counts = HashMap::new();
+    for op in nodes.values().filter_map(Value::op) {
+        *counts.entry(op).or_insert(0) += 1;
+    }
+    counts
+}
+
+/// `(label, data, grad)` of every learnable leaf (`NodeKind::Param`) in the
+/// graph rooted at `root`, ordered as in `print_tree`. Constants and inputs
+/// are left out, so it gives a flat view of the trainable state.
+pub fn leaf_table(root: &Value) -> Vec<(Option<String>, f64, f64)> {
+    let (nodes, _) = trace(root);
+    (0..nodes.len())
+        .map(|id| &nodes[&id])
+        .filter(|v| v.kind() == NodeKind::Param)
+        .map(|v| (v.label(), v.data(), v.grad()))
+        .collect()
+}
+
+/// Table of every labeled node in the graph rooted at `root` with its data
+/// and gradient, ordered as in `print_tree`, for a quick look after
+/// `backward` without drawing the graph:
+///
+/// ```text
+/// label |    data |    grad
+/// ------+---------+--------
+/// o     |  0.7071 |  1.0000
+/// n     |  0.8814 |  0.5000
+/// ```
+pub fn grad_report(root: &Value) -> String {
+    let (nodes, _) = trace(root);
+    let rows: Vec<(String, String, String)> = (0..nodes.len())
+        .map(|id| &nodes[&id])
+        .filter_map(|v| {
+            let label = v.label()?;
+            Some((
+                label,
+                format!("{:.4}", v.data()),
+                format!("{:.4}", v.grad()),
+            ))
+        })
+        .collect();
+
+    let width = |header: &str, col: fn(&(String, String, String)) -> &String| {
+        rows.iter()
+            .map(|r| col(r).chars().count())
+            .fold(header.len(), usize::max)
+    };
+    let (lw, dw, gw) = (
+        width("label", |r| &r.0),
+        width("data", |r| &r.1),
+        width("grad", |r| &r.2),
+    );
+
+    let mut out = format!("{:<lw$} | {:>dw$} | {:>gw$}\n", "label", "data", "grad");
+    out.push_str(&format!(
+        "{}-+-{}-+-{}\n",
+        "-".repeat(lw),
+        "-".repeat(dw),
+        "-".repeat(gw)
+    ));
+    for (label, data, grad) in &rows {
+        out.push_str(&format!("{label:<lw$} | {data:>dw$} | {grad:>gw$}\n"));
+    }
+    out
+}
+
+/// Human-readable structural and data differences between the graphs rooted
+/// at `a` and `b`, e.g. `"node 0 op differs: * vs +"`. Empty when the graphs
+/// are identical.
+///
+/// Nodes are numbered as in `print_tree` and compared pairwise by number, so
+/// once the shapes diverge later nodes may be reported as well.
+pub fn graph_diff(a: &Value, b: &Value) -> Vec<String> {
+    fn op_name(v: &Value) -> &'static str {
+        v.op().unwrap_or("leaf")
+    }
+
+    let (nodes_a, edges_a) = trace(a);
+    let (nodes_b, edges_b) = trace(b);
+    let children = |edges: &[Edge], n: usize| {
+        let mut children = vec![Vec::new(); n];
+        for &(child, parent, _) in edges {
+            children[parent].push(child);
+        }
+        children
+    };
+    let children_a = children(&edges_a, nodes_a.len());
+    let children_b = children(&edges_b, nodes_b.len());
+
+    let mut diffs = Vec::new();
+    if nodes_a.len() != nodes_b.len() {
+        diffs.push(format!(
+            "node count differs: {} vs {}",
+            nodes_a.len(),
+            nodes_b.len()
+        ));
+    }
+    for id in 0..nodes_a.len().min(nodes_b.len()) {
+        let (x, y) = (&nodes_a[&id], &nodes_b[&id]);
+        if x.op() != y.op() {
+            diffs.push(format!(
+                "node {} op differs: {} vs {}",
+                id,
+                op_name(x),
+                op_name(y)
+            ));
+        }
+        if x.data() != y.data() {
+            diffs.push(format!(
+                "node {} data differs: {} vs {}",
+                id,
+                x.data(),
+                y.data()
+            ));
+        }
+        if x.label() != y.label() {
+            diffs.push(format!(
+                "node {} label differs: {:?} vs {:?}",
+                id,
+                x.label(),
+                y.label()
+            ));
+        }
+        if children_a[id] != children_b[id] {
+            diffs.push(format!(
+                "node {} inputs differ: {:?} vs {:?}",
+                id, children_a[id], children_b[id]
+            ));
+        }
+    }
+    diffs
+}
+
+const SVG_NODE_WIDTH: usize = 220;
+const SVG_NODE_HEIGHT: usize = 36;
+const SVG_LAYER_GAP: usize = 60;
+const SVG_ROW_GAP: usize = 16;
+const SVG_MARGIN: usize = 10;
+
+fn svg_fill(kind: NodeKind) -> &'static str {
+    match kind {
+        NodeKind::Op | NodeKind::Const => "white",
+        NodeKind::Input => "lightblue",
+        NodeKind::Param => "lightyellow",
+    }
+}
+
+fn xml_escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Render the computation graph rooted at `root` as a standalone SVG document
+/// without graphviz, so it works where no `dot` binary is installed.
+///
+/// The layout is a plain layered one, left to right like `to_dot`: leaves sit
+/// in the first column and every other node one column right of its deepest
+/// input. Each node is a `<g class="node">` box showing its label, op, data
+/// and grad, and each edge a line from an input to the node using it.
+pub fn render_svg(root: &Value) -> String {
+    let (nodes, edges) = trace(root);
+    let n = nodes.len();
+    let mut children = vec![Vec::new(); n];
+    for &(child, parent, _) in &edges {
+        children[parent].push(child);
+    }
+
+    fn layer_of(id: usize, children: &[Vec<usize>], layers: &mut [Option<usize>]) -> usize {
+        if let Some(layer) = layers[id] {
+            return layer;
+        }
+        let layer = children[id]
+            .iter()
+            .map(|&child| layer_of(child, children, layers) + 1)
+            .max()
+            .unwrap_or(0);
+        layers[id] = Some(layer);
+        layer
+    }
+    let mut layers = vec![None; n];
+    let mut rows_used: Vec<usize> = Vec::new();
+    let position: Vec<(usize, usize)> = (0..n)
+        .map(|id| {
+            let layer = layer_of(id, &children, &mut layers);
+            if rows_used.len() <= layer {
+                rows_used.resize(layer + 1, 0);
+            }
+            let row = rows_used[layer];
+            rows_used[layer] += 1;
+            (
+                SVG_MARGIN + layer * (SVG_NODE_WIDTH + SVG_LAYER_GAP),
+                SVG_MARGIN + row * (SVG_NODE_HEIGHT + SVG_ROW_GAP),
+            )
+        })
+        .collect();
+
+    let width = 2 * SVG_MARGIN + rows_used.len() * (SVG_NODE_WIDTH + SVG_LAYER_GAP) - SVG_LAYER_GAP;
+    let height = 2 * SVG_MARGIN
+        + rows_used.iter().max().copied().unwrap_or(0) * (SVG_NODE_HEIGHT + SVG_ROW_GAP)
+        - SVG_ROW_GAP;
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{}\" height=\"{}\" font-family=\"monospace\" font-size=\"11\">\n",
+        width, height
+    );
+
+    for (child, parent, _) in &edges {
+        let (cx, cy) = position[*child];
+        let (px, py) = position[*parent];
+        svg.push_str(&format!(
+            "<line class=\"edge\" x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\" stroke=\"gray\"/>\n",
+            cx + SVG_NODE_WIDTH,
+            cy + SVG_NODE_HEIGHT / 2,
+            px,
+            py + SVG_NODE_HEIGHT / 2
+        ));
+    }
+
+    for (id, (x, y)) in position.iter().enumerate() {
+        let val = &nodes[&id];
+        let dash = if val.kind() == NodeKind::Const {
+            " stroke-dasharray=\"4 2\""
+        } else {
+            ""
+        };
+        svg.push_str(&format!(
+            "<g class=\"node\"><rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\" stroke=\"black\"{}/>",
+            x,
+            y,
+            SVG_NODE_WIDTH,
+            SVG_NODE_HEIGHT,
+            svg_fill(val.kind()),
+            dash
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">{}</text>",
+            x + 6,
+            y + 14,
+            xml_escape(&describe(val))
+        ));
+        svg.push_str(&format!(
+            "<text x=\"{}\" y=\"{}\">data {:.4} | grad {:.4}</text></g>\n",
+            x + 6,
+            y + 29,
+            val.data(),
+            val.grad()
+        ));
+    }
+
+    svg.push_str("</svg>\n");
+    svg
+}
+
+/// Writes `render_svg(root)` to `output_path`, a graphviz-free `draw_dot`
+pub fn draw_svg(root: &Value, output_path: &str) {
+    std::fs::write(output_path, render_svg(root)).expect("Failed to write graph");
+}
+
+pub fn draw_dot(root: &Value, output_path: &str) {
+    draw_dot_with_options(root, output_path, &DotOptions::default());
+}
+
+pub fn draw_dot_with_options(root: &Value, output_path: &str, options: &DotOptions) {
+    let dot_string = to_dot(root, options);
 
     let graph = graphviz_rust::parse(&dot_string).expect("Failed to parse dot");
     graphviz_rust::exec(
@@ -0,0 +1,155 @@
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, Normal};
+
+use crate::engine::Value;
+use crate::nn::{MLP, Module, mse_loss};
+use crate::optim::Optimizer;
+
+/// Callback invoked with `(epoch, train_loss)` once an epoch completes
+pub type EpochCallback = Box<dyn FnMut(usize, f64)>;
+
+/// Training loop for an `MLP` on a mean squared error objective, generic over
+/// the update rule
+pub struct Trainer<O: Optimizer> {
+    optimizer: O,
+    epochs: usize,
+    callbacks: Vec<EpochCallback>,
+    grad_noise: Option<GradNoise>,
+}
+
+/// Annealed Gaussian noise added to the gradients before each optimizer step
+struct GradNoise {
+    std: f64,
+    decay: f64,
+    rng: StdRng,
+    /// Optimizer steps taken so far
+    t: usize,
+}
+
+impl GradNoise {
+    /// Adds `N(0, std / (1 + t)^decay)` to the gradient of every parameter
+    fn apply(&mut self, params: &[Value]) {
+        let std = self.std / (1.0 + self.t as f64).powf(self.decay);
+        let normal = Normal::new(0.0, std).expect("noise std must be finite and non-negative");
+        for p in params {
+            p.set_grad(normal.sample(&mut self.rng));
+        }
+        self.t += 1;
+    }
+}
+
+impl<O: Optimizer> Trainer<O> {
+    /// `optimizer` should have been built over the parameters of the model passed to `fit`
+    pub fn new(optimizer: O, epochs: usize) -> Self {
+        Trainer {
+            optimizer,
+            epochs,
+            callbacks: Vec::new(),
+            grad_noise: None,
+        }
+    }
+
+    /// Adds annealed gradient noise as a regularizer: before step `t` (from
+    /// 0), every parameter gradient gets a sample of `N(0, std / (1 + t)^decay)`
+    /// drawn from an RNG seeded with `seed`, so runs are reproducible.
+    pub fn with_grad_noise(mut self, std: f64, decay: f64, seed: u64) -> Self {
+        self.grad_noise = Some(GradNoise {
+            std,
+            decay,
+            rng: StdRng::seed_from_u64(seed),
+            t: 0,
+        });
+        self
+    }
+
+    pub fn optimizer(&self) -> &O {
+        &self.optimizer
+    }
+
+    /// Registers a callback fired at the end of every epoch, e.g. for logging or early stopping
+    pub fn on_epoch_end(&mut self, callback: impl FnMut(usize, f64) + 'static) {
+        self.callbacks.push(Box::new(callback));
+    }
+
+    /// Trains `model` on `(xs, ys)` and returns the loss of every epoch
+    pub fn fit(&mut self, model: &MLP, xs: &[Vec<Value>], ys: &[Value]) -> Vec<f64> {
+        (0..self.epochs)
+            .map(|epoch| {
+                let loss = self.train_epoch(model, xs, ys);
+                for callback in self.callbacks.iter_mut() {
+                    callback(epoch, loss);
+                }
+                loss
+            })
+            .collect()
+    }
+
+    /// One full-batch forward, backward and optimizer step, returning the loss
+    fn train_epoch(&mut self, model: &MLP, xs: &[Vec<Value>], ys: &[Value]) -> f64 {
+        let y_pred = xs.iter().flat_map(|x| model.forward(x)).collect::<Vec<_>>();
+        let loss = mse_loss(&y_pred, ys);
+
+        self.optimizer.zero_grad();
+        loss.backward();
+        if let Some(noise) = &mut self.grad_noise {
+            noise.apply(&model.parameters());
+        }
+        self.optimizer.step();
+
+        loss.data()
+    }
+
+    /// Learning rate finder: trains for `epochs` steps at each of `num_lrs`
+    /// learning rates spaced geometrically across `lr_range` and returns the
+    /// `(lr, final loss)` pairs.
+    ///
+    /// The model parameters are restored to their initial values and the
+    /// optimizer is reset before every trial and once the search is over, so
+    /// each learning rate starts from the same point and both the model and
+    /// the optimizer's learning rate are left untouched. Epoch callbacks are
+    /// not fired.
+    pub fn lr_find(
+        &mut self,
+        model: &MLP,
+        xs: &[Vec<Value>],
+        ys: &[Value],
+        lr_range: (f64, f64),
+        num_lrs: usize,
+    ) -> Vec<(f64, f64)> {
+        assert!(self.epochs > 0, "lr_find requires at least one epoch");
+        let (min_lr, max_lr) = lr_range;
+        let ratio = if num_lrs > 1 {
+            (max_lr / min_lr).powf(1.0 / (num_lrs - 1) as f64)
+        } else {
+            1.0
+        };
+
+        let params = model.parameters();
+        let initial: Vec<f64> = params.iter().map(Value::data).collect();
+        let initial_lr = self.optimizer.lr();
+        let restore = |optimizer: &mut O| {
+            for (param, &data) in params.iter().zip(initial.iter()) {
+                param.set_data(data);
+            }
+            model.zero_grad();
+            optimizer.reset();
+        };
+
+        let results = (0..num_lrs)
+            .map(|i| {
+                restore(&mut self.optimizer);
+                let lr = min_lr * ratio.powi(i as i32);
+                self.optimizer.set_lr(lr);
+                let mut loss = f64::NAN;
+                for _ in 0..self.epochs {
+                    loss = self.train_epoch(model, xs, ys);
+                }
+                (lr, loss)
+            })
+            .collect();
+        restore(&mut self.optimizer);
+        self.optimizer.set_lr(initial_lr);
+        results
+    }
+}
@@ -1,4 +1,9 @@
-use micrograd::engine::Value;
+use micrograd::assert_value_near;
+use micrograd::engine::{
+    BackwardError, NodeKind, Value, backward_vector, checkpoint, data_vec, gather, jacobian,
+    logsumexp, mul_add, nan_check_enabled, set_nan_check, sum_cols, sum_rows,
+};
+use std::collections::HashSet;
 
 #[test]
 fn test_value_creation() {
@@ -119,6 +124,60 @@ fn test_gradient_accumulation() {
     );
 }
 
+fn assert_rel_close(actual: f64, expected: f64, x: f64, what: &str) {
+    let tol = 1e-12 * expected.abs().max(f64::MIN_POSITIVE);
+    assert!(
+        (actual - expected).abs() <= tol,
+        "{what} at x = {x}: expected {expected:e}, got {actual:e}"
+    );
+}
+
+#[test]
+fn test_tanh_matches_reference_over_grid() {
+    // -20.0, -19.75, ..., 20.0, covering the fully saturated tails. The
+    // references come from std: `cosh` stays accurate over this range, so
+    // `1 / cosh^2` is a trustworthy derivative even where it is ~1e-17.
+    for k in -80..=80 {
+        let x = k as f64 * 0.25;
+        let (t, dt) = (x.tanh(), 1.0 / x.cosh().powi(2));
+
+        let a = Value::new(x);
+        let b = a.tanh();
+        b.backward();
+
+        assert_rel_close(b.data(), t, x, "tanh");
+        assert_rel_close(a.grad(), dt, x, "tanh grad");
+    }
+}
+
+#[test]
+fn test_tanh_grad_matches_high_precision_table() {
+    // sech^2(x) evaluated with 60 significant digits, rounded to f64
+    let table = [
+        (0.5, 0.7864477329659274),
+        (3.0, 0.00986603716544019),
+        (10.0, 8.244614455767397e-09),
+        (20.0, 1.6993417021166355e-17),
+        (30.0, 3.502604305078608e-26),
+    ];
+    for (x, dt) in table {
+        for x in [x, -x] {
+            let a = Value::new(x);
+            a.tanh().backward();
+            assert_rel_close(a.grad(), dt, x, "tanh grad");
+        }
+    }
+}
+
+#[test]
+fn test_tanh_grad_nonzero_in_saturation() {
+    let a = Value::new(20.0);
+    a.tanh().backward();
+    // sech^2(20) ~ 1.7e-17, while 1 - tanh(20)^2 rounds to 0
+    assert!(a.grad() > 0.0);
+    assert!((a.grad() - 1.6993417021166355e-17).abs() < 1e-28);
+}
+
 #[test]
 fn test_value_power() {
     let a = Value::new(2.0);
@@ -154,6 +213,29 @@ fn test_backward_power() {
     );
 }
 
+#[test]
+fn test_backward_power_saturates_on_overflow() {
+    // 400 * 10^399 overflows f64, so the gradient saturates instead of
+    // becoming inf
+    let a = Value::new(10.0);
+    a.powi(400).backward();
+    assert_eq!(a.grad(), f64::MAX);
+
+    // the sign of the exact gradient is kept: 400 * (-10)^399 < 0
+    let b = Value::new(-10.0);
+    b.powi(400).backward();
+    assert_eq!(b.grad(), -f64::MAX);
+}
+
+#[test]
+fn test_backward_power_zero_exponent_at_zero() {
+    let a = Value::new(0.0);
+    let b = a.powi(0);
+    b.backward();
+    assert_eq!(b.data(), 1.0);
+    assert_eq!(a.grad(), 0.0);
+}
+
 #[test]
 fn test_value_exponential() {
     let a = Value::new(2.0);
@@ -294,3 +376,772 @@ fn test_tanh_backprop_equivalence() {
         grad_manual
     );
 }
+
+#[test]
+fn test_value_identity_equality() {
+    let a = Value::new(1.0);
+
+    assert_eq!(a.clone(), a, "Expected a clone to be the same node");
+    assert_ne!(
+        Value::new(1.0),
+        Value::new(1.0),
+        "Expected distinct nodes with equal data to differ"
+    );
+}
+
+#[test]
+fn test_value_hash_set() {
+    let a = Value::new(1.0);
+    let b = Value::new(1.0);
+
+    let set: HashSet<Value> = [a.clone(), a.clone(), b.clone()].into_iter().collect();
+    assert_eq!(set.len(), 2);
+    assert!(set.contains(&a));
+    assert!(set.contains(&b));
+}
+
+#[test]
+fn test_value_relu6() {
+    for (x, expected, expected_grad) in [
+        (-2.0, 0.0, 0.0),
+        (0.0, 0.0, 0.0),
+        (3.5, 3.5, 1.0),
+        (6.0, 6.0, 0.0),
+        (8.0, 6.0, 0.0),
+    ] {
+        let a = Value::new(x);
+        let b = a.relu6();
+        b.backward();
+
+        assert_eq!(b.data(), expected, "Expected relu6({}) to be {}", x, expected);
+        assert_eq!(b.op(), Some("relu6"), "Expected op to be 'relu6'");
+        assert_eq!(
+            a.grad(),
+            expected_grad,
+            "Expected grad of relu6 at {} to be {}",
+            x,
+            expected_grad
+        );
+    }
+}
+
+#[test]
+fn test_value_log() {
+    let a = Value::new(2.0);
+    let b = a.log();
+
+    assert!((b.data() - 2.0_f64.ln()).abs() < 1e-8);
+    assert_eq!(b.op(), Some("log"), "Expected op to be 'log'");
+
+    b.backward();
+    // ∂ln(x)/∂x = 1/x
+    assert!((a.grad() - 0.5).abs() < 1e-8);
+}
+
+#[test]
+fn test_backward_checked_ok() {
+    let a = Value::new(2.0);
+    let b = Value::new(3.0);
+    let c = a.clone() * b.clone();
+
+    assert!(c.backward_checked().is_ok());
+    assert_eq!(a.grad(), 3.0);
+    assert!(a.is_finite());
+}
+
+#[test]
+fn test_backward_checked_reports_nan() {
+    let a = Value::new(-1.0);
+    a.set_label("a".to_string());
+    let w = Value::new(2.0);
+    w.set_label("w".to_string());
+    // log of a negative number is NaN, which leaks into w's gradient
+    let out = a.log() * w.clone();
+
+    match out.backward_checked() {
+        Err(BackwardError::NonFiniteGradient { label, grad, .. }) => {
+            assert_eq!(label, Some("w".to_string()));
+            assert!(grad.is_nan());
+        }
+        other => panic!("Expected a non-finite gradient error, got {other:?}"),
+    }
+    assert!(!w.is_finite());
+}
+
+fn matrix() -> Vec<Vec<Value>> {
+    vec![
+        vec![Value::new(1.0), Value::new(2.0), Value::new(3.0)],
+        vec![Value::new(4.0), Value::new(5.0), Value::new(6.0)],
+    ]
+}
+
+#[test]
+fn test_sum_rows() {
+    let m = matrix();
+    let sums = sum_rows(&m);
+
+    assert_eq!(sums.iter().map(|v| v.data()).collect::<Vec<_>>(), vec![6.0, 15.0]);
+
+    sums[0].backward();
+    // only the first row contributes to the first row sum
+    for v in &m[0] {
+        assert_eq!(v.grad(), 1.0);
+    }
+    for v in &m[1] {
+        assert_eq!(v.grad(), 0.0);
+    }
+}
+
+#[test]
+fn test_sum_cols() {
+    let m = matrix();
+    let sums = sum_cols(&m);
+
+    assert_eq!(
+        sums.iter().map(|v| v.data()).collect::<Vec<_>>(),
+        vec![5.0, 7.0, 9.0]
+    );
+
+    sums[1].backward();
+    // only the middle column contributes to the middle column sum
+    for row in &m {
+        assert_eq!(row[0].grad(), 0.0);
+        assert_eq!(row[1].grad(), 1.0);
+        assert_eq!(row[2].grad(), 0.0);
+    }
+}
+
+#[test]
+fn test_backward_through_join_of_subgraphs_sharing_a_leaf() {
+    let a = Value::new(1.5);
+    let left = a.clone() * Value::new(2.0);
+    let right = a.clone() + Value::new(3.0);
+    let joined = left * right;
+
+    joined.backward();
+    // d/da [2a * (a + 3)] = 2(a + 3) + 2a = 4a + 6
+    assert_eq!(joined.data(), 13.5);
+    assert!((a.grad() - 12.0).abs() < 1e-12);
+}
+
+#[test]
+fn test_backward_through_join_of_disjoint_subgraphs() {
+    let pred = Value::new(2.0) * Value::new(3.0);
+    let target = Value::new(4.0);
+    let diff = pred.clone() - target.clone();
+    let loss = diff.clone() * diff;
+
+    loss.backward();
+    // loss = (pred - target)^2 = 4
+    assert_eq!(loss.data(), 4.0);
+    assert_eq!(pred.grad(), 4.0);
+    assert_eq!(target.grad(), -4.0);
+}
+
+#[test]
+fn test_backward_gradients_are_bit_identical_across_runs() {
+    // a shared leaf fanning into many terms, so its gradient is a long sum
+    // whose rounding depends on the accumulation order
+    let run = |padding: usize| -> Vec<u64> {
+        // shift where the graph's nodes are allocated between runs
+        let _padding: Vec<Value> = (0..padding).map(|i| Value::new(i as f64)).collect();
+        let x = Value::new(0.3);
+        let ws: Vec<Value> = (1..=50).map(|i| Value::new(1.0 / i as f64)).collect();
+        let loss = ws
+            .iter()
+            .map(|w| (x.clone() * w.clone()).tanh() * x.clone())
+            .reduce(|acc, t| acc + t)
+            .unwrap();
+        loss.backward();
+        std::iter::once(&x)
+            .chain(&ws)
+            .map(|v| v.grad().to_bits())
+            .collect()
+    };
+
+    let first = run(0);
+    for padding in [1, 7, 64, 513] {
+        assert_eq!(run(padding), first);
+    }
+}
+
+#[test]
+fn test_graph_depth() {
+    let x1 = Value::new(2.0);
+    let w1 = Value::new(-3.0);
+    let x2 = Value::new(0.0);
+    let w2 = Value::new(1.0);
+    let b = Value::new(6.88);
+
+    assert_eq!(x1.graph_depth(), 0);
+
+    // tanh -> (+ b) -> (+) -> (*) -> x1
+    let o = (x1 * w1 + x2 * w2 + b).tanh();
+    assert_eq!(o.graph_depth(), 4);
+}
+
+#[test]
+fn test_exp_clamped() {
+    let a = Value::new(1000.0);
+    let b = a.exp_clamped(50.0);
+    b.backward();
+
+    assert!(a.exp().data().is_infinite());
+    assert_eq!(b.data(), 50.0_f64.exp());
+    assert!(a.grad().is_finite());
+    assert_eq!(a.grad(), 0.0);
+
+    // below the clamp it matches exp exactly
+    let c = Value::new(2.0);
+    let d = c.exp_clamped(50.0);
+    d.backward();
+    assert_eq!(d.data(), 2.0_f64.exp());
+    assert_eq!(c.grad(), 2.0_f64.exp());
+}
+
+#[test]
+fn test_backward_with_seed_scales_gradients() {
+    let build = || {
+        let a = Value::new(2.0);
+        let b = Value::new(-3.0);
+        let out = (a.clone() * b.clone() + a.clone()).tanh();
+        (a, b, out)
+    };
+
+    let (a1, b1, out1) = build();
+    out1.backward();
+
+    let (a2, b2, out2) = build();
+    out2.backward_with_seed(2.0);
+
+    assert_eq!(out2.grad(), 2.0);
+    assert!((a2.grad() - 2.0 * a1.grad()).abs() < 1e-12);
+    assert!((b2.grad() - 2.0 * b1.grad()).abs() < 1e-12);
+}
+
+#[test]
+fn test_backward_no_seed_uses_existing_grad() {
+    let a = Value::new(2.0);
+    let out = a.clone() * Value::new(3.0);
+
+    out.set_grad(0.5);
+    out.backward_no_seed();
+    assert_eq!(out.grad(), 0.5);
+    assert_eq!(a.grad(), 1.5);
+}
+
+#[test]
+fn test_recip() {
+    let a = Value::new(4.0);
+    let b = a.recip();
+    b.backward();
+
+    assert_eq!(b.data(), 0.25);
+    assert_eq!(b.op(), Some("recip"));
+    // d(1/x)/dx = -1/x^2
+    assert_eq!(a.grad(), -1.0 / 16.0);
+}
+
+#[test]
+#[should_panic(expected = "recip of zero")]
+fn test_recip_of_zero_panics() {
+    Value::new(0.0).recip();
+}
+
+#[test]
+fn test_div() {
+    let a = Value::new(3.0);
+    let b = Value::new(4.0);
+    let c = a.clone() / b.clone();
+    c.backward();
+
+    assert_eq!(c.data(), 0.75);
+    assert_eq!(a.grad(), 0.25);
+    // d(a/b)/db = -a/b^2
+    assert_eq!(b.grad(), -3.0 / 16.0);
+}
+
+#[test]
+fn test_div_count() {
+    let a = Value::new(3.0);
+    let mean = a.div_count(4);
+    mean.backward();
+
+    assert_eq!(mean.data(), 0.75);
+    assert_eq!(a.grad(), 0.25);
+}
+
+#[test]
+#[should_panic(expected = "cannot divide by a count of zero")]
+fn test_div_count_of_zero_panics() {
+    Value::new(1.0).div_count(0);
+}
+
+#[test]
+fn test_checkpoint_matches_plain_gradients() {
+    // a segment with two outputs that also closes over a weight
+    fn segment(x: &[Value], w: &Value) -> Vec<Value> {
+        let h = (x[0].clone() * w.clone() + x[1].clone()).tanh();
+        vec![h.clone() * x[0].clone(), h.exp()]
+    }
+
+    let run = |checkpointed: bool| {
+        let a = Value::new(0.7);
+        let b = Value::new(-1.2);
+        let w = Value::new(0.4);
+        let x = vec![a.clone() * Value::new(2.0), b.clone()];
+
+        let outs = if checkpointed {
+            let w = w.clone();
+            checkpoint(&x, move |x| segment(x, &w))
+        } else {
+            segment(&x, &w)
+        };
+        let loss = outs[0].clone() * outs[1].clone() + a.clone();
+        loss.backward();
+        (loss.data(), a.grad(), b.grad(), w.grad())
+    };
+
+    let (plain_loss, plain_a, plain_b, plain_w) = run(false);
+    let (ckpt_loss, ckpt_a, ckpt_b, ckpt_w) = run(true);
+
+    assert!((plain_loss - ckpt_loss).abs() < 1e-12);
+    assert!((plain_a - ckpt_a).abs() < 1e-12);
+    assert!((plain_b - ckpt_b).abs() < 1e-12);
+    assert!((plain_w - ckpt_w).abs() < 1e-12);
+}
+
+#[test]
+fn test_checkpoint_does_not_keep_intermediates() {
+    let x = vec![Value::new(1.0), Value::new(2.0)];
+    let outs = checkpoint(&x, |x| vec![(x[0].clone() * x[1].clone()).tanh()]);
+
+    assert_eq!(outs[0].op(), Some("checkpoint"));
+    assert_eq!(outs[0].prev(), x);
+    assert_eq!(outs[0].graph_depth(), 1);
+}
+
+#[test]
+fn test_backward_vector() {
+    let a = Value::new(2.0);
+    let b = Value::new(-3.0);
+    let y1 = a.clone() * b.clone();
+    let y2 = a.clone() + b.clone().powi(2);
+
+    backward_vector(&[y1, y2], &[2.0, 3.0]).unwrap();
+    // 2 * dy1/da + 3 * dy2/da = 2b + 3
+    assert_eq!(a.grad(), 2.0 * -3.0 + 3.0);
+    // 2 * dy1/db + 3 * dy2/db = 2a + 3 * 2b
+    assert_eq!(b.grad(), 2.0 * 2.0 + 3.0 * 2.0 * -3.0);
+}
+
+#[test]
+fn test_backward_vector_length_mismatch() {
+    let y = Value::new(1.0) * Value::new(2.0);
+    assert!(matches!(
+        backward_vector(&[y], &[1.0, 2.0]),
+        Err(BackwardError::LengthMismatch {
+            outputs: 1,
+            grads: 2
+        })
+    ));
+}
+
+#[test]
+fn test_abs() {
+    for (x, grad) in [(-2.5, -1.0), (3.0, 1.0), (0.0, 0.0)] {
+        let a = Value::new(x);
+        let b = a.abs();
+        b.backward();
+        assert_eq!(b.data(), x.abs());
+        assert_eq!(a.grad(), grad);
+    }
+}
+
+#[test]
+fn test_sqrt() {
+    let a = Value::new(4.0);
+    let b = a.sqrt();
+    b.backward();
+    assert_eq!(b.data(), 2.0);
+    assert_eq!(a.grad(), 0.25);
+
+    // matches central differences at another positive point
+    let x = 2.7;
+    let a = Value::new(x);
+    a.sqrt().backward();
+    let h = 1e-6;
+    let numeric = ((x + h).sqrt() - (x - h).sqrt()) / (2.0 * h);
+    assert!((a.grad() - numeric).abs() < 1e-8);
+}
+
+#[test]
+fn test_sqrt_edge_cases() {
+    let a = Value::new(-1.0);
+    assert!(a.sqrt().data().is_nan());
+
+    let z = Value::new(0.0);
+    let out = z.sqrt();
+    out.backward();
+    assert_eq!(out.data(), 0.0);
+    assert_eq!(z.grad(), f64::INFINITY);
+}
+
+#[test]
+fn test_logsumexp() {
+    let zeros: Vec<Value> = (0..3).map(|_| Value::new(0.0)).collect();
+    assert!((logsumexp(&zeros).data() - 3.0_f64.ln()).abs() < 1e-12);
+
+    // large inputs would overflow a naive exp
+    let big = [Value::new(1000.0), Value::new(1000.0)];
+    assert!((logsumexp(&big).data() - (1000.0 + 2.0_f64.ln())).abs() < 1e-9);
+
+    let data = [1.0, -0.5, 2.0];
+    let xs: Vec<Value> = data.iter().map(|&x| Value::new(x)).collect();
+    logsumexp(&xs).backward();
+
+    let total: f64 = data.iter().map(|x| x.exp()).sum();
+    let lse = |d: &[f64]| d.iter().map(|x| x.exp()).sum::<f64>().ln();
+    let h = 1e-6;
+    for (i, x) in xs.iter().enumerate() {
+        // the gradient is the softmax of the inputs
+        assert!((x.grad() - data[i].exp() / total).abs() < 1e-12);
+
+        let mut plus = data;
+        plus[i] += h;
+        let mut minus = data;
+        minus[i] -= h;
+        let numeric = (lse(&plus) - lse(&minus)) / (2.0 * h);
+        assert!((x.grad() - numeric).abs() < 1e-6);
+    }
+}
+
+#[test]
+fn test_backward_create_graph_second_derivative() {
+    let x = Value::new(2.0);
+    let y = x.powi(3);
+
+    let dy = y.backward_create_graph().unwrap().get(&x).unwrap();
+    assert!((dy.data() - 3.0 * 4.0).abs() < 1e-12);
+    assert_eq!(x.grad(), 0.0, "create_graph must not touch grad");
+
+    // d²(x³)/dx² = 6x
+    dy.backward();
+    assert!((x.grad() - 6.0 * 2.0).abs() < 1e-12);
+
+    // the same through repeated products
+    let x = Value::new(-1.5);
+    let y = x.clone() * x.clone() * x.clone();
+    y.backward_create_graph().unwrap().get(&x).unwrap().backward();
+    assert!((x.grad() - 6.0 * -1.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_backward_create_graph_tanh() {
+    let x = Value::new(0.7);
+    let t = 0.7_f64.tanh();
+
+    let dy = x.tanh().backward_create_graph().unwrap().get(&x).unwrap();
+    assert!((dy.data() - (1.0 - t * t)).abs() < 1e-12);
+    dy.backward();
+    assert!((x.grad() - -2.0 * t * (1.0 - t * t)).abs() < 1e-12);
+}
+
+#[test]
+fn test_backward_create_graph_unsupported_op() {
+    let x = Value::new(1.0);
+    let y = checkpoint(std::slice::from_ref(&x), |xs| vec![xs[0].tanh()]);
+
+    assert!(matches!(
+        y[0].backward_create_graph(),
+        Err(BackwardError::UnsupportedOp("checkpoint"))
+    ));
+}
+
+#[test]
+fn test_rounding_helpers() {
+    let cases = [
+        (2.5, 1.0, 2.0, 3.0, 3.0),
+        (-2.5, -1.0, -3.0, -2.0, -3.0),
+        (1.2, 1.0, 1.0, 2.0, 1.0),
+        (-0.7, -1.0, -1.0, -0.0, -1.0),
+        (0.0, 0.0, 0.0, 0.0, 0.0),
+    ];
+    for (x, sign, floor, ceil, round) in cases {
+        let v = Value::new(x);
+        assert_eq!(v.sign().data(), sign);
+        assert_eq!(v.floor().data(), floor);
+        assert_eq!(v.ceil().data(), ceil);
+        assert_eq!(v.round().data(), round);
+    }
+}
+
+#[test]
+fn test_rounding_helpers_are_detached() {
+    let x = Value::new(1.7);
+    let rounded = [x.sign(), x.floor(), x.ceil(), x.round()];
+    for r in &rounded {
+        assert!(r.prev().is_empty());
+        assert_eq!(r.op(), None);
+    }
+
+    let y = rounded
+        .into_iter()
+        .reduce(|acc, r| acc + r)
+        .unwrap()
+        * x.clone();
+    y.backward();
+    // only the direct use of x contributes: d(c * x)/dx = c = 1 + 1 + 2 + 2
+    assert_eq!(x.grad(), 6.0);
+
+    let z = Value::new(0.3);
+    z.round().backward();
+    assert_eq!(z.grad(), 0.0);
+}
+
+#[test]
+fn test_node_kind() {
+    let x = Value::new(2.0).as_input();
+    let w = Value::new(-1.0);
+    let c = Value::constant(3.0);
+    let y = x.clone() * w.clone() + c.clone();
+
+    assert_eq!(x.kind(), NodeKind::Input);
+    assert_eq!(w.kind(), NodeKind::Param);
+    assert_eq!(c.kind(), NodeKind::Const);
+    assert_eq!(y.kind(), NodeKind::Op);
+    assert!(!c.requires_grad());
+
+    // constants never accumulate a gradient
+    y.backward();
+    assert_eq!(w.grad(), 2.0);
+    assert_eq!(x.grad(), -1.0);
+    assert_eq!(c.grad(), 0.0);
+}
+
+#[test]
+fn test_backward_twice_on_intermediate_does_not_compound() {
+    let x = Value::new(0.5);
+    let h = x.clone() * x.clone();
+    let m = h.tanh();
+    let loss = m.clone() * Value::new(3.0);
+
+    m.backward();
+    let once = x.grad();
+    assert!((once - (1.0 - 0.25_f64.tanh().powi(2)) * 2.0 * 0.5).abs() < 1e-12);
+
+    // a second pass adds exactly one more gradient at every node
+    m.backward();
+    assert!((x.grad() - 2.0 * once).abs() < 1e-12);
+    assert!((h.grad() - 2.0 * (1.0 - 0.25_f64.tanh().powi(2))).abs() < 1e-12);
+    assert_eq!(m.grad(), 2.0);
+
+    // backward on the larger graph is not thrown off by the intermediate's gradient
+    x.zero_grad();
+    h.zero_grad();
+    m.zero_grad();
+    m.backward();
+    loss.backward();
+    assert!((x.grad() - 4.0 * once).abs() < 1e-12);
+}
+
+#[test]
+fn test_gather_accumulates_repeated_indices() {
+    let table: Vec<Value> = [0.5, -1.0, 2.0].iter().map(|&x| Value::new(x)).collect();
+
+    let rows = gather(&table, &[2, 0, 2]);
+    assert_eq!(
+        rows.iter().map(Value::data).collect::<Vec<_>>(),
+        vec![2.0, 0.5, 2.0]
+    );
+
+    let loss = rows[0].clone() * Value::new(3.0)
+        + rows[1].clone() * Value::new(4.0)
+        + rows[2].clone() * Value::new(5.0);
+    loss.backward();
+
+    // table[2] is used at two positions: 3 + 5
+    assert_eq!(table[2].grad(), 8.0);
+    assert_eq!(table[0].grad(), 4.0);
+    assert_eq!(table[1].grad(), 0.0);
+}
+
+#[test]
+#[should_panic(expected = "index 3 out of range")]
+fn test_gather_out_of_range() {
+    let table = vec![Value::new(1.0)];
+    gather(&table, &[0, 3]);
+}
+
+#[test]
+fn test_jacobian_of_linear_map_is_weight_matrix() {
+    let w = [[2.0, -1.0], [0.5, 3.0]];
+    let x: Vec<Value> = [1.5, -2.0].iter().map(|&v| Value::new(v)).collect();
+    x[0].set_grad(7.0);
+
+    let y: Vec<Value> = w
+        .iter()
+        .map(|row| {
+            Value::new(row[0]) * x[0].clone() + Value::new(row[1]) * x[1].clone() + Value::new(1.0)
+        })
+        .collect();
+
+    let jac = jacobian(&y, &x);
+    assert_eq!(jac, vec![vec![2.0, -1.0], vec![0.5, 3.0]]);
+    // the inputs keep the gradients they had before
+    assert_eq!(x[0].grad(), 7.0);
+    assert_eq!(x[1].grad(), 0.0);
+}
+
+#[test]
+fn test_add_vec_and_mul_vec_match_scalar_ops() {
+    let build = || -> (Vec<Value>, Vec<Value>) {
+        let a = (0..5).map(|i| Value::new(i as f64 - 2.0)).collect();
+        let b = (0..5).map(|i| Value::new(0.5 * i as f64 + 1.0)).collect();
+        (a, b)
+    };
+
+    let (a, b) = build();
+    let bulk = Value::mul_vec(&Value::add_vec(&a, &b), &b);
+    let (a2, b2) = build();
+    let scalar: Vec<Value> = a2
+        .iter()
+        .zip(&b2)
+        .map(|(x, y)| (x.clone() + y.clone()) * y.clone())
+        .collect();
+
+    for (p, q) in bulk.iter().zip(&scalar) {
+        assert_eq!(p.data(), q.data());
+    }
+    bulk.iter().for_each(|v| v.tanh().backward());
+    scalar.iter().for_each(|v| v.tanh().backward());
+    for (x, y) in a.iter().chain(&b).zip(a2.iter().chain(&b2)) {
+        assert!((x.grad() - y.grad()).abs() < 1e-12);
+    }
+    assert_eq!(bulk[0].op(), Some("*"));
+    assert_eq!(bulk[0].grad_contributions(), scalar[0].grad_contributions());
+}
+
+#[test]
+#[should_panic(expected = "cannot apply '+' to slices of length 2 and 1")]
+fn test_add_vec_length_mismatch() {
+    Value::add_vec(&[Value::new(1.0), Value::new(2.0)], &[Value::new(3.0)]);
+}
+
+/// Timing comparison behind the numbers in the `Value::add_vec` docs, run with
+/// `cargo test --release -- --ignored --nocapture test_add_vec_timing`
+#[test]
+#[ignore]
+fn test_add_vec_timing() {
+    let n = 100_000;
+    let a: Vec<Value> = (0..n).map(|i| Value::new(i as f64)).collect();
+    let b: Vec<Value> = (0..n).map(|i| Value::new(-(i as f64))).collect();
+
+    // best of several runs, building the nodes only
+    let best = |f: &dyn Fn() -> Vec<Value>| {
+        (0..5)
+            .map(|_| {
+                let start = std::time::Instant::now();
+                let out = f();
+                (start.elapsed(), out)
+            })
+            .min_by_key(|(elapsed, _)| *elapsed)
+            .unwrap()
+    };
+    let (scalar_time, scalar) = best(&|| {
+        a.iter()
+            .zip(&b)
+            .map(|(x, y)| x.clone() + y.clone())
+            .collect()
+    });
+    let (bulk_time, bulk) = best(&|| Value::add_vec(&a, &b));
+
+    println!("{n} adds: scalar {scalar_time:?}, add_vec {bulk_time:?}");
+    assert_eq!(data_vec(&scalar), data_vec(&bulk));
+}
+
+#[test]
+fn test_approx_eq_tolerance_boundary() {
+    let v = Value::new(1.0);
+    assert!(v.approx_eq(1.0, 0.0));
+    // 0.5 and 1.5 are exact in binary, so the difference is exactly the tolerance
+    assert!(v.approx_eq(1.5, 0.5));
+    assert!(v.approx_eq(0.5, 0.5));
+    assert!(!v.approx_eq(1.5 + 1e-12, 0.5));
+    assert!(!v.approx_eq(f64::NAN, 1.0));
+    assert!(!Value::new(f64::NAN).approx_eq(1.0, f64::INFINITY));
+
+    assert_value_near!(v, 1.0 + 1e-9);
+    assert_value_near!(v.clone() * Value::new(3.0), 2.95, 0.1);
+}
+
+#[test]
+#[should_panic(expected = "value 1 is not within 0.00000001 of 1.1")]
+fn test_assert_value_near_panics_outside_tolerance() {
+    assert_value_near!(Value::new(1.0), 1.1);
+}
+
+#[test]
+#[should_panic(expected = "op 'log' produced NaN from inputs [-2.0]")]
+fn test_nan_check_panics_at_the_producing_op() {
+    set_nan_check(true);
+    let x = Value::new(-2.0);
+    let _ = (x.log() * Value::new(3.0)).exp();
+}
+
+#[test]
+fn test_nan_check_is_off_by_default() {
+    assert!(!nan_check_enabled());
+    assert!(Value::new(-2.0).log().data().is_nan());
+
+    set_nan_check(true);
+    // finite results pass, including through binary ops
+    let y = Value::new(2.0).log() + Value::new(1.0);
+    assert!(y.data().is_finite());
+    let overflow = std::panic::catch_unwind(|| Value::new(1e308) * Value::new(10.0));
+    assert!(overflow.is_err());
+    set_nan_check(false);
+}
+
+#[test]
+fn test_mul_add() {
+    let f = |a: f64, b: f64, c: f64| (a * b + c).tanh();
+    let (a0, b0, c0) = (1.5, -0.7, 0.3);
+    let a = Value::new(a0);
+    let b = Value::new(b0);
+    let c = Value::new(c0);
+
+    let fused = mul_add(&a, &b, &c);
+    assert_eq!(fused.op(), Some("mul_add"));
+    assert_eq!(fused.prev(), vec![a.clone(), b.clone(), c.clone()]);
+    assert!((fused.data() - (a0 * b0 + c0)).abs() < 1e-15);
+
+    // gradients agree with central finite differences
+    fused.tanh().backward();
+    let h = 1e-6;
+    let numeric = [
+        (f(a0 + h, b0, c0) - f(a0 - h, b0, c0)) / (2.0 * h),
+        (f(a0, b0 + h, c0) - f(a0, b0 - h, c0)) / (2.0 * h),
+        (f(a0, b0, c0 + h) - f(a0, b0, c0 - h)) / (2.0 * h),
+    ];
+    for (v, n) in [&a, &b, &c].iter().zip(numeric) {
+        assert!((v.grad() - n).abs() < 1e-8, "{} vs {}", v.grad(), n);
+    }
+
+    // the same derivatives as building it from `*` and `+`
+    let a2 = Value::new(a0);
+    (a2.clone() * Value::new(b0) + Value::new(c0))
+        .tanh()
+        .backward();
+    assert!((a.grad() - a2.grad()).abs() < 1e-12);
+
+    // second derivative through create_graph: d²(a * b + c)/da db = 1
+    let da = mul_add(&a, &b, &c)
+        .backward_create_graph()
+        .unwrap()
+        .get(&a)
+        .unwrap();
+    b.zero_grad();
+    da.backward();
+    assert_eq!(b.grad(), 1.0);
+}
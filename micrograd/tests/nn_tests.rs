@@ -1,5 +1,13 @@
-use micrograd::engine::Value;
-use micrograd::nn::{Layer, MLP, Module, Neuron};
+use micrograd::engine::{Value, data_vec, grad_vec};
+use micrograd::nn::{
+    BatchNorm1d, Dropout, ExportedMlp, ImportError, Layer, MLP, Module, Neuron, Reduction,
+    TANH_GAIN, activation_stats, add_bias, add_bias_batch, add_scalar_bias, batch_cross_entropy,
+    batch_cross_entropy_with, huber_loss, huber_loss_with, kl_divergence, mse_loss, mse_loss_with,
+    scaled_dot_product_attention, zero_grad_inputs,
+};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use rand_distr::{Distribution, StandardNormal};
 
 #[test]
 fn test_neuron_forward() {
@@ -9,6 +17,21 @@ fn test_neuron_forward() {
     assert!(output.data() >= -1.0 && output.data() <= 1.0);
 }
 
+#[test]
+fn test_neuron_pre_activation() {
+    let n = Neuron::new(2);
+    // parameters are [w0, w1, b]
+    for (param, data) in n.parameters().iter().zip([0.5, -2.0, 0.25]) {
+        param.set_data(data);
+    }
+    let x = vec![Value::new(3.0), Value::new(1.0)];
+
+    let pre = n.pre_activation(&x);
+    // 0.5 * 3 + (-2) * 1 + 0.25
+    assert_eq!(pre.data(), -0.25);
+    assert_eq!(n.forward(&x).data(), (-0.25_f64).tanh());
+}
+
 #[test]
 fn test_layer_forward() {
     let layer = Layer::new(3, 2);
@@ -20,6 +43,22 @@ fn test_layer_forward() {
     }
 }
 
+#[test]
+fn test_data_vec_and_grad_vec() {
+    let layer = Layer::new(3, 4);
+    let out = layer.forward(&values(&[1.0, -2.0, 0.5]));
+
+    let data = data_vec(&out);
+    assert_eq!(data.len(), 4);
+    for (d, v) in data.iter().zip(&out) {
+        assert_eq!(*d, v.data());
+    }
+
+    assert_eq!(grad_vec(&out), vec![0.0; 4]);
+    out[1].backward();
+    assert_eq!(grad_vec(&out), vec![0.0, 1.0, 0.0, 0.0]);
+}
+
 #[test]
 fn test_mlp_forward() {
     let mlp = MLP::new(3, vec![4, 2, 1]);
@@ -32,17 +71,687 @@ fn test_mlp_forward() {
 }
 
 #[test]
+fn test_mlp_forward_with_activations() {
+    let mlp = MLP::new(3, vec![4, 2, 1]);
+    let input = vec![Value::new(0.5), Value::new(-1.0), Value::new(2.0)];
+
+    let activations = mlp.forward_with_activations(&input);
+    assert_eq!(activations.len(), 3);
+    let widths: Vec<usize> = activations.iter().map(|a| a.len()).collect();
+    assert_eq!(widths, vec![4, 2, 1]);
+    assert_eq!(activations[2][0].data(), mlp.forward(&input)[0].data());
+}
+
+#[test]
+fn test_activation_stats() {
+    let activations = vec![
+        vec![Value::new(1.0), Value::new(2.0), Value::new(3.0), Value::new(4.0)],
+        vec![Value::new(-0.5), Value::new(0.5)],
+        vec![Value::new(0.9), Value::new(0.9), Value::new(0.9)],
+    ];
+
+    let stats = activation_stats(&activations);
+    assert_eq!(stats.len(), 3);
+    assert!((stats[0].0 - 2.5).abs() < 1e-12);
+    assert!((stats[0].1 - 1.25_f64.sqrt()).abs() < 1e-12);
+    assert!((stats[1].0 - 0.0).abs() < 1e-12);
+    assert!((stats[1].1 - 0.5).abs() < 1e-12);
+    assert!((stats[2].0 - 0.9).abs() < 1e-12);
+    assert!(stats[2].1.abs() < 1e-12);
+}
+
+#[test]
+#[allow(clippy::identity_op)]
 fn test_parameters() {
     let mlp = MLP::new(3, vec![4, 4, 1]);
     let params = mlp.parameters();
     assert_eq!(params.len(), 3 * 4 + 4 * 4 + 4 * 1 + 4 + 4 + 1); // weights + biases is 41
 }
 
+#[test]
+fn test_layers_and_neurons() {
+    let nouts = vec![4, 4, 1];
+    let mlp = MLP::new(3, nouts.clone());
+
+    assert_eq!(mlp.layers().len(), nouts.len());
+    for (layer, &nout) in mlp.layers().iter().zip(&nouts) {
+        assert_eq!(layer.neurons().len(), nout as usize);
+    }
+    assert_eq!((&mlp).into_iter().count(), nouts.len());
+    let params: usize = (&mlp).into_iter().map(|l| l.num_parameters()).sum();
+    assert_eq!(params, mlp.num_parameters());
+}
+
+#[test]
+fn test_num_parameters() {
+    let sizes = [3, 4, 4, 1];
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let expected: usize = sizes.windows(2).map(|w| (w[0] + 1) * w[1]).sum();
+
+    assert_eq!(mlp.num_parameters(), expected);
+    assert_eq!(mlp.num_parameters(), mlp.parameters().len());
+    assert_eq!(Neuron::new(5).num_parameters(), 6);
+    assert_eq!(Layer::new(3, 2).num_parameters(), 8);
+}
+
+#[test]
+fn test_weight_histogram() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let hist = mlp.weight_histogram(10);
+
+    assert_eq!(hist.len(), 10);
+    // weights and biases are initialized uniformly in [-1, 1]
+    assert!(hist.iter().all(|&(edge, _)| (-1.0..=1.0).contains(&edge)));
+    assert!(hist.windows(2).all(|w| w[0].0 < w[1].0));
+    let total: usize = hist.iter().map(|&(_, count)| count).sum();
+    assert_eq!(total, mlp.num_parameters());
+}
+
 #[test]
 fn test_zero_grad() {
     let mlp = MLP::new(3, vec![4, 4, 1]);
+    let x = vec![Value::new(1.0), Value::new(-2.0), Value::new(0.5)];
+    mlp.forward(&x)[0].backward();
     mlp.zero_grad();
     for param in mlp.parameters() {
         assert_eq!(param.grad(), 0.0);
     }
 }
+
+#[test]
+fn test_dropout_train_and_eval() {
+    let dropout = Dropout::new(0.5);
+    let input: Vec<Value> = (0..100).map(|_| Value::new(1.0)).collect();
+
+    assert!(dropout.is_training());
+    let output = dropout.forward(&input);
+    assert!(output.iter().all(|v| v.data() == 0.0 || v.data() == 2.0));
+    assert!(output.iter().any(|v| v.data() == 0.0));
+
+    dropout.set_training(false);
+    let output = dropout.forward(&input);
+    assert!(output.iter().all(|v| v.data() == 1.0));
+}
+
+fn batch(rows: &[[f64; 2]]) -> Vec<Vec<Value>> {
+    rows.iter().map(|row| values(row)).collect()
+}
+
+#[test]
+fn test_batch_norm_updates_running_stats_in_train_mode() {
+    let bn = BatchNorm1d::new(2);
+    let x = batch(&[[1.0, 10.0], [2.0, 20.0], [3.0, 30.0]]);
+
+    let out = bn.forward(&x);
+    // feature means 2 and 20, unbiased variances 1 and 100
+    let mean = bn.running_mean();
+    let var = bn.running_var();
+    assert!((mean[0] - 0.1 * 2.0).abs() < 1e-12);
+    assert!((mean[1] - 0.1 * 20.0).abs() < 1e-12);
+    assert!((var[0] - (0.9 + 0.1 * 1.0)).abs() < 1e-12);
+    assert!((var[1] - (0.9 + 0.1 * 100.0)).abs() < 1e-12);
+
+    // the batch itself is normalized with its own statistics
+    for j in 0..2 {
+        let column: Vec<f64> = out.iter().map(|row| row[j].data()).collect();
+        assert!(column.iter().sum::<f64>().abs() < 1e-9);
+        assert!((column[2] - 1.0).abs() < 1e-4);
+    }
+
+    bn.forward(&x);
+    assert!((bn.running_mean()[0] - (0.9 * 0.2 + 0.1 * 2.0)).abs() < 1e-12);
+}
+
+#[test]
+fn test_batch_norm_eval_uses_running_stats() {
+    let bn = BatchNorm1d::with_momentum(2, 0.5, 0.0);
+    bn.forward(&batch(&[[1.0, 10.0], [3.0, 30.0]]));
+    let (mean, var) = (bn.running_mean(), bn.running_var());
+
+    bn.set_training(false);
+    let x = batch(&[[4.0, 5.0]]);
+    let out = bn.forward(&x);
+    assert_eq!(bn.running_mean(), mean, "eval must not update running stats");
+    for j in 0..2 {
+        let expected = (x[0][j].data() - mean[j]) / var[j].sqrt();
+        assert!((out[0][j].data() - expected).abs() < 1e-12);
+    }
+
+    // gamma and beta still receive gradients in eval mode
+    out[0][0].backward();
+    assert_eq!(bn.parameters().len(), 4);
+    assert!((bn.parameters()[0].grad() - (4.0 - mean[0]) / var[0].sqrt()).abs() < 1e-12);
+    assert_eq!(bn.parameters()[2].grad(), 1.0);
+}
+
+#[test]
+fn test_mlp_with_dropout_mode() {
+    let mlp = MLP::with_dropout(3, vec![8, 8, 1], 0.5);
+    let input = vec![Value::new(0.5), Value::new(-1.0), Value::new(2.0)];
+
+    mlp.set_training(false);
+    assert!(!mlp.is_training());
+    let expected = mlp.forward(&input)[0].data();
+    for _ in 0..5 {
+        assert_eq!(mlp.forward(&input)[0].data(), expected);
+    }
+
+    mlp.set_training(true);
+    assert!(mlp.is_training());
+    let outputs: Vec<f64> = (0..20).map(|_| mlp.forward(&input)[0].data()).collect();
+    assert!(outputs.iter().any(|&o| o != outputs[0]));
+}
+
+#[test]
+fn test_add_bias() {
+    let xs = vec![Value::new(1.0), Value::new(2.0)];
+    let bias = vec![Value::new(0.5), Value::new(-1.0)];
+
+    let out = add_bias(&xs, &bias);
+    let data: Vec<f64> = out.iter().map(Value::data).collect();
+    assert_eq!(data, vec![1.5, 1.0]);
+}
+
+#[test]
+#[should_panic(expected = "same length")]
+fn test_add_bias_length_mismatch() {
+    add_bias(&[Value::new(1.0)], &[Value::new(0.5), Value::new(-1.0)]);
+}
+
+#[test]
+fn test_add_bias_batch_accumulates_grad_over_examples() {
+    let examples = batch(&[[1.0, 2.0], [-3.0, 4.0]]);
+    let bias = values(&[0.5, -1.0]);
+
+    let out = add_bias_batch(&examples, &bias);
+    let data: Vec<Vec<f64>> = out.iter().map(|row| data_vec(row)).collect();
+    assert_eq!(data, vec![vec![1.5, 1.0], vec![-2.5, 3.0]]);
+
+    // loss = sum over examples of 2 * out[0] + 3 * out[1]
+    let loss = out
+        .iter()
+        .flat_map(|row| {
+            [
+                row[0].clone() * Value::new(2.0),
+                row[1].clone() * Value::new(3.0),
+            ]
+        })
+        .reduce(|acc, x| acc + x)
+        .unwrap();
+    loss.backward();
+    // each bias element receives its gradient once per example
+    assert_eq!(grad_vec(&bias), vec![4.0, 6.0]);
+    assert_eq!(grad_vec(&examples[1]), vec![2.0, 3.0]);
+}
+
+#[test]
+fn test_add_scalar_bias_accumulates_grad() {
+    let xs = vec![Value::new(1.0), Value::new(2.0), Value::new(3.0)];
+    let bias = Value::new(0.5);
+
+    let out = add_scalar_bias(&xs, &bias);
+    let data: Vec<f64> = out.iter().map(Value::data).collect();
+    assert_eq!(data, vec![1.5, 2.5, 3.5]);
+
+    // sum of 2 * out[i], so each element sends a gradient of 2 to the bias
+    let loss = out
+        .into_iter()
+        .fold(Value::new(0.0), |acc, o| acc + o * Value::new(2.0));
+    loss.backward();
+    assert_eq!(bias.grad(), 6.0);
+}
+
+#[test]
+fn test_zero_grad_inputs() {
+    let mlp = MLP::new(2, vec![3, 1]);
+    let x = vec![Value::new(0.5), Value::new(-1.0)];
+
+    mlp.forward(&x)[0].backward();
+    let expected: Vec<f64> = x.iter().map(Value::grad).collect();
+
+    // a second iteration over the same inputs without zeroing doubles their grads
+    mlp.zero_grad();
+    mlp.forward(&x)[0].backward();
+    for (input, &g) in x.iter().zip(&expected) {
+        assert!((input.grad() - 2.0 * g).abs() < 1e-12);
+    }
+
+    mlp.zero_grad();
+    zero_grad_inputs(&x);
+    mlp.forward(&x)[0].backward();
+    for (input, &g) in x.iter().zip(&expected) {
+        assert!((input.grad() - g).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_forward_quantized_error_grows_with_fewer_bits() {
+    let mlp = MLP::new(3, vec![8, 8, 1]);
+    let inputs = [[2.0, 3.0, -1.0], [3.0, -1.0, 0.5], [0.5, 1.0, 1.0], [1.0, 1.0, -1.0]];
+
+    let error = |bits: u8| -> f64 {
+        inputs
+            .iter()
+            .map(|x| {
+                let values: Vec<Value> = x.iter().map(|&v| Value::new(v)).collect();
+                let full = mlp.forward(&values)[0].data();
+                (mlp.forward_quantized(x, bits)[0] - full).abs()
+            })
+            .sum()
+    };
+
+    assert!(error(32) < 1e-6);
+    assert!(error(16) < error(8));
+    assert!(error(8) < error(3));
+}
+
+fn values(xs: &[f64]) -> Vec<Value> {
+    xs.iter().map(|&x| Value::new(x)).collect()
+}
+
+#[test]
+fn test_kl_divergence_with_itself_is_zero() {
+    let p = values(&[0.7, 0.2, 0.1]);
+    let q = values(&[0.7, 0.2, 0.1]);
+    assert!(kl_divergence(&p, &q).data().abs() < 1e-9);
+}
+
+#[test]
+fn test_kl_divergence_handles_zeros() {
+    let p = values(&[1.0, 0.0]);
+    let q = values(&[0.5, 0.0]);
+    let kl = kl_divergence(&p, &q);
+    assert!((kl.data() - 2.0_f64.ln()).abs() < 1e-9);
+
+    let kl = kl_divergence(&values(&[0.5, 0.5]), &values(&[1.0, 0.0]));
+    assert!(kl.data().is_finite());
+}
+
+#[test]
+fn test_kl_divergence_grad_moves_q_toward_p() {
+    let p = values(&[0.7, 0.2, 0.1]);
+    let q = values(&[0.2, 0.3, 0.5]);
+    let kl = kl_divergence(&p, &q);
+    kl.backward();
+
+    // one gradient step on q, renormalized to a distribution
+    let stepped: Vec<f64> = q.iter().map(|qi| qi.data() - 0.01 * qi.grad()).collect();
+    let total: f64 = stepped.iter().sum();
+    let q_new = values(&stepped.iter().map(|x| x / total).collect::<Vec<_>>());
+
+    assert!(kl_divergence(&p, &q_new).data() < kl.data());
+}
+
+#[test]
+fn test_layer_grad_norms() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    assert!(mlp.layer_grad_norms().iter().all(|&n| n == 0.0));
+
+    let xs = [[2.0, 3.0, -1.0], [3.0, -1.0, 0.5], [0.5, 1.0, 1.0], [1.0, 1.0, -1.0]];
+    let preds: Vec<Value> = xs.iter().flat_map(|x| mlp.forward(&values(x))).collect();
+    mse_loss(&preds, &values(&[1.0, -1.0, -1.0, 1.0])).backward();
+
+    let norms = mlp.layer_grad_norms();
+    assert_eq!(norms.len(), 3);
+    assert!(norms.iter().all(|n| n.is_finite() && *n >= 0.0));
+    assert!(norms[2] > 0.0, "Expected the output layer to receive gradient");
+}
+
+#[test]
+fn test_update_ratios_per_layer() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let xs = [[2.0, 3.0, -1.0], [3.0, -1.0, 0.5], [0.5, 1.0, 1.0], [1.0, 1.0, -1.0]];
+    let preds: Vec<Value> = xs.iter().flat_map(|x| mlp.forward(&values(x))).collect();
+    mse_loss(&preds, &values(&[1.0, -1.0, -1.0, 1.0])).backward();
+
+    let ratios = mlp.update_ratios(0.01);
+    assert_eq!(ratios.len(), 3);
+    assert!(ratios.iter().all(|r| r.is_finite() && *r > 0.0));
+}
+
+#[test]
+fn test_reinit_preserves_identity() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let before = mlp.parameters();
+    let data_before: Vec<f64> = before.iter().map(Value::data).collect();
+
+    mlp.reinit(&mut StdRng::seed_from_u64(0));
+
+    let after = mlp.parameters();
+    let data_after: Vec<f64> = after.iter().map(Value::data).collect();
+    for (b, a) in before.iter().zip(&after) {
+        assert_eq!(b.ptr(), a.ptr());
+    }
+    assert_ne!(data_before, data_after);
+    assert!(data_after.iter().all(|x| (-1.0..=1.0).contains(x)));
+}
+
+#[test]
+fn test_reinit_keeps_init_gain_scale() {
+    let mlp = MLP::with_init_gain(30, vec![50, 1], TANH_GAIN);
+    mlp.reinit(&mut StdRng::seed_from_u64(0));
+
+    let first = &mlp.layers()[0];
+    let weights: Vec<f64> = first
+        .neurons()
+        .iter()
+        .flat_map(|n| n.parameters().into_iter().take(30))
+        .map(|w| w.data())
+        .collect();
+    let n = weights.len() as f64;
+    let std = (weights.iter().map(|w| w * w).sum::<f64>() / n).sqrt();
+    // 1500 draws from N(0, (gain / sqrt(30))^2), far narrower than uniform [-1, 1]
+    let expected = TANH_GAIN / 30f64.sqrt();
+    assert!(
+        (std - expected).abs() < 0.1 * expected,
+        "weight std {std} is not close to {expected}"
+    );
+    let biases: Vec<f64> = mlp
+        .layers()
+        .iter()
+        .flat_map(|l| l.neurons())
+        .map(|n| n.parameters().last().unwrap().data())
+        .collect();
+    assert!(biases.iter().all(|&b| b == 0.0));
+}
+
+#[test]
+fn test_batch_norm_reinit_resets_to_identity() {
+    let bn = BatchNorm1d::new(2);
+    bn.forward(&batch(&[[1.0, 2.0], [3.0, 6.0]]));
+    for param in bn.parameters() {
+        param.set_data(0.5);
+    }
+
+    bn.reinit(&mut StdRng::seed_from_u64(0));
+    let data: Vec<f64> = bn.parameters().iter().map(Value::data).collect();
+    assert_eq!(data, vec![1.0, 1.0, 0.0, 0.0]);
+    assert_eq!(bn.running_mean(), vec![0.0, 0.0]);
+    assert_eq!(bn.running_var(), vec![1.0, 1.0]);
+}
+
+/// Gradient of `huber_loss` w.r.t. `pred`, by backward and by central differences
+fn huber_grads(pred: f64, target: f64, delta: f64) -> (f64, f64) {
+    let p = Value::new(pred);
+    huber_loss(&p, &Value::new(target), delta).backward();
+
+    let h = 1e-6;
+    let at = |x: f64| huber_loss(&Value::new(x), &Value::new(target), delta).data();
+    (p.grad(), (at(pred + h) - at(pred - h)) / (2.0 * h))
+}
+
+#[test]
+fn test_huber_loss_regimes() {
+    let target = Value::new(1.0);
+
+    // quadratic for small errors
+    assert!((huber_loss(&Value::new(1.5), &target, 1.0).data() - 0.125).abs() < 1e-12);
+    // linear for large ones: 1 * (3 - 0.5)
+    assert!((huber_loss(&Value::new(4.0), &target, 1.0).data() - 2.5).abs() < 1e-12);
+    assert!((huber_loss(&Value::new(-2.0), &target, 1.0).data() - 2.5).abs() < 1e-12);
+    // both pieces agree at the transition
+    assert!((huber_loss(&Value::new(2.0), &target, 1.0).data() - 0.5).abs() < 1e-12);
+}
+
+#[test]
+fn test_huber_loss_grad_check() {
+    for (pred, expected) in [(1.5, 0.5), (4.0, 1.0), (-2.0, -1.0)] {
+        let (analytic, numeric) = huber_grads(pred, 1.0, 1.0);
+        assert!((analytic - expected).abs() < 1e-12);
+        assert!((analytic - numeric).abs() < 1e-6);
+    }
+    // at the transition the slope is delta from either side
+    let (analytic, _) = huber_grads(2.0, 1.0, 1.0);
+    assert_eq!(analytic, 1.0);
+}
+
+#[test]
+fn test_batch_cross_entropy() {
+    let raw = [[2.0, 1.0, 0.1], [0.5, 0.5, 3.0]];
+    let targets = [0, 1];
+    let logits: Vec<Vec<Value>> = raw.iter().map(|row| values(row)).collect();
+
+    let loss = batch_cross_entropy(&logits, &targets);
+    let softmax = |row: &[f64; 3]| {
+        let total: f64 = row.iter().map(|x| x.exp()).sum();
+        row.map(|x| x.exp() / total)
+    };
+    let expected = -(softmax(&raw[0])[0].ln() + softmax(&raw[1])[1].ln()) / 2.0;
+    assert!((loss.data() - expected).abs() < 1e-12);
+
+    loss.backward();
+    for (i, row) in logits.iter().enumerate() {
+        let probs = softmax(&raw[i]);
+        for (j, logit) in row.iter().enumerate() {
+            let one_hot = if j == targets[i] { 1.0 } else { 0.0 };
+            assert!((logit.grad() - (probs[j] - one_hot) / 2.0).abs() < 1e-12);
+        }
+        // descending the gradient raises the target logit
+        assert!(row[targets[i]].grad() < 0.0);
+    }
+}
+
+#[test]
+#[should_panic(expected = "target 3 out of range")]
+fn test_batch_cross_entropy_target_out_of_range() {
+    batch_cross_entropy(&[values(&[0.0, 1.0, 2.0])], &[3]);
+}
+
+#[test]
+fn test_residual_layer_with_zero_weights_is_identity() {
+    let mlp = MLP::with_residual(2, vec![2, 2, 1]);
+    for param in mlp.layers()[0].parameters() {
+        param.set_data(0.0);
+    }
+
+    let x = values(&[0.3, -0.8]);
+    let activations = mlp.forward_with_activations(&x);
+    assert_eq!(activations[0][0].data(), 0.3);
+    assert_eq!(activations[0][1].data(), -0.8);
+    // the output layer changes the size and gets no residual
+    assert_eq!(activations[2].len(), 1);
+    assert!(activations[2][0].data().abs() < 1.0);
+}
+
+#[test]
+fn test_residual_gradients_flow_through_both_paths() {
+    let mlp = MLP::with_residual(1, vec![1]);
+    let params = mlp.parameters();
+    let (w, b) = (params[0].clone(), params[1].clone());
+    w.set_data(0.5);
+    b.set_data(0.1);
+
+    let x = Value::new(2.0);
+    let out = mlp.forward(std::slice::from_ref(&x));
+    let t = (0.5_f64 * 2.0 + 0.1).tanh();
+    assert!((out[0].data() - (t + 2.0)).abs() < 1e-12);
+
+    out[0].backward();
+    // identity path contributes 1, the layer w * (1 - t^2)
+    assert!((x.grad() - (1.0 + 0.5 * (1.0 - t * t))).abs() < 1e-12);
+    assert!((w.grad() - 2.0 * (1.0 - t * t)).abs() < 1e-12);
+    assert!((b.grad() - (1.0 - t * t)).abs() < 1e-12);
+}
+
+#[test]
+fn test_mlp_export_json_roundtrip() {
+    let mlp = MLP::with_residual(3, vec![4, 4, 2]);
+    let json = mlp.export_json();
+
+    let exported: ExportedMlp = serde_json::from_str(&json).unwrap();
+    assert_eq!(exported.nin, 3);
+    assert!(exported.residual);
+    let dims: Vec<(usize, usize)> = exported.layers.iter().map(|l| (l.nin, l.nout)).collect();
+    assert_eq!(dims, vec![(3, 4), (4, 4), (4, 2)]);
+    for layer in &exported.layers {
+        assert_eq!(layer.activation, "tanh");
+        assert_eq!(layer.weights.len(), layer.nin * layer.nout);
+        assert_eq!(layer.bias.len(), layer.nout);
+    }
+
+    let imported = MLP::import_json(&json).unwrap();
+    assert_eq!(imported.num_parameters(), mlp.num_parameters());
+    let x = values(&[0.3, -1.2, 0.8]);
+    assert_eq!(data_vec(&imported.forward(&x)), data_vec(&mlp.forward(&x)));
+}
+
+#[test]
+fn test_mlp_import_json_keeps_init_scheme() {
+    let json = MLP::with_init_gain(30, vec![50, 1], TANH_GAIN).export_json();
+    let exported: ExportedMlp = serde_json::from_str(&json).unwrap();
+    let expected = TANH_GAIN / 30f64.sqrt();
+    assert!((exported.layers[0].init_std.unwrap() - expected).abs() < 1e-12);
+    assert!(!MLP::new(2, vec![1]).export_json().contains("init_std"));
+
+    let imported = MLP::import_json(&json).unwrap();
+    imported.reinit(&mut StdRng::seed_from_u64(0));
+    let weights: Vec<f64> = imported.layers()[0]
+        .neurons()
+        .iter()
+        .flat_map(|n| n.parameters().into_iter().take(30))
+        .map(|w| w.data())
+        .collect();
+    let std = (weights.iter().map(|w| w * w).sum::<f64>() / weights.len() as f64).sqrt();
+    assert!(
+        (std - expected).abs() < 0.1 * expected,
+        "weight std {std} is not close to {expected}"
+    );
+}
+
+#[test]
+fn test_mlp_import_json_rejects_bad_shapes() {
+    let json = MLP::new(2, vec![3, 1]).export_json();
+    let mut exported: ExportedMlp = serde_json::from_str(&json).unwrap();
+    exported.layers[1].weights.pop();
+
+    let err = MLP::import_json(&serde_json::to_string(&exported).unwrap()).err();
+    assert!(matches!(
+        err,
+        Some(ImportError::Shape {
+            layer: 1,
+            field: "weights",
+            expected: 3,
+            got: 2
+        })
+    ));
+    assert!(matches!(MLP::import_json("{"), Err(ImportError::Json(_))));
+}
+
+#[test]
+fn test_init_gain_keeps_pre_activations_unsaturated() {
+    let mut rng = StdRng::seed_from_u64(0);
+    let xs: Vec<Vec<Value>> = (0..50)
+        .map(|_| {
+            (0..100)
+                .map(|_| Value::new(StandardNormal.sample(&mut rng)))
+                .collect()
+        })
+        .collect();
+    // standard deviation and fraction of saturated tanh outputs of the first layer
+    let stats = |mlp: &MLP| {
+        let pre: Vec<f64> = xs
+            .iter()
+            .flat_map(|x| mlp.layers()[0].neurons().iter().map(|n| n.pre_activation(x).data()))
+            .collect();
+        let n = pre.len() as f64;
+        let mean = pre.iter().sum::<f64>() / n;
+        let std = (pre.iter().map(|p| (p - mean).powi(2)).sum::<f64>() / n).sqrt();
+        let saturated = pre.iter().filter(|p| p.tanh().abs() > 0.97).count() as f64 / n;
+        (std, saturated)
+    };
+
+    let (std, saturated) = stats(&MLP::with_init_gain(100, vec![50, 1], 1.0));
+    assert!((std - 1.0).abs() < 0.2, "std {std}");
+    assert!(saturated < 0.05);
+
+    let (tanh_std, tanh_saturated) = stats(&MLP::with_init_gain(100, vec![50, 1], TANH_GAIN));
+    assert!((tanh_std - TANH_GAIN).abs() < 0.3, "std {tanh_std}");
+
+    // uniform [-1, 1] weights scale with sqrt(fan_in / 3), about 5.8 here
+    let (uniform_std, uniform_saturated) = stats(&MLP::new(100, vec![50, 1]));
+    assert!(uniform_std > 4.0, "std {uniform_std}");
+    assert!(uniform_saturated > 2.0 * tanh_saturated);
+}
+
+#[test]
+fn test_loss_reductions() {
+    let preds = values(&[0.5, -1.0, 2.0]);
+    let targets = values(&[1.0, 1.0, -0.5]);
+    let logits = vec![values(&[1.0, 2.0, 0.5]), values(&[-1.0, 0.0, 3.0])];
+    let classes = [1, 0];
+
+    let all = |reduction| {
+        [
+            mse_loss_with(&preds, &targets, reduction),
+            huber_loss_with(&preds, &targets, 1.0, reduction),
+            batch_cross_entropy_with(&logits, &classes, reduction),
+        ]
+    };
+    let (mean, sum, none) = (
+        all(Reduction::Mean),
+        all(Reduction::Sum),
+        all(Reduction::None),
+    );
+
+    for ((mean, sum), none) in mean.iter().zip(&sum).zip(&none) {
+        assert_eq!((mean.len(), sum.len()), (1, 1));
+        let n = none.len() as f64;
+        assert!((sum[0].data() - mean[0].data() * n).abs() < 1e-12);
+        assert!((sum[0].data() - data_vec(none).iter().sum::<f64>()).abs() < 1e-12);
+    }
+    assert_eq!(data_vec(&none[0]), vec![0.25, 4.0, 6.25]);
+    assert_eq!(data_vec(&none[1]), vec![0.125, 1.5, 2.0]);
+    assert_eq!(none[2].len(), 2);
+    assert_eq!(mean[0][0].data(), mse_loss(&preds, &targets).data());
+    assert_eq!(
+        mean[2][0].data(),
+        batch_cross_entropy(&logits, &classes).data()
+    );
+
+    // per-example losses keep their own gradients
+    none[0][1].backward();
+    assert_eq!(grad_vec(&preds), vec![0.0, -4.0, 0.0]);
+}
+
+#[test]
+fn test_scaled_dot_product_attention() {
+    let matrix =
+        |rows: [[f64; 2]; 2]| -> Vec<Vec<Value>> { rows.iter().map(|r| values(r)).collect() };
+    let attend = |q00: f64| {
+        let q = matrix([[q00, 0.0], [0.0, 1.0]]);
+        let k = matrix([[1.0, 0.0], [0.0, 1.0]]);
+        let v = matrix([[1.0, 2.0], [3.0, 4.0]]);
+        let out = scaled_dot_product_attention(&q, &k, &v);
+        (q, out)
+    };
+
+    let (q, out) = attend(1.0);
+    // row 0 scores are [1/sqrt(2), 0], so it weights v[0] by their sigmoid
+    let w = 1.0 / (1.0 + (-std::f64::consts::FRAC_1_SQRT_2).exp());
+    assert_eq!(out.len(), 2);
+    assert!((out[0][0].data() - (3.0 - 2.0 * w)).abs() < 1e-12);
+    assert!((out[0][1].data() - (4.0 - 2.0 * w)).abs() < 1e-12);
+    assert!((out[1][0].data() - (1.0 + 2.0 * w)).abs() < 1e-12);
+
+    out[0][0].backward();
+    let h = 1e-6;
+    let numeric = (attend(1.0 + h).1[0][0].data() - attend(1.0 - h).1[0][0].data()) / (2.0 * h);
+    assert!(
+        (q[0][0].grad() - numeric).abs() < 1e-6,
+        "analytic {} vs numeric {}",
+        q[0][0].grad(),
+        numeric
+    );
+}
+
+#[test]
+#[should_panic(expected = "attention needs at least one key")]
+fn test_scaled_dot_product_attention_without_keys() {
+    scaled_dot_product_attention(&[values(&[1.0])], &[], &[]);
+}
+
+#[test]
+#[should_panic(expected = "attention needs keys of at least one dimension")]
+fn test_scaled_dot_product_attention_zero_width() {
+    scaled_dot_product_attention(&[vec![]], &[vec![]], &[values(&[1.0])]);
+}
@@ -0,0 +1,78 @@
+use micrograd::engine::{Value, checkpoint, grad_vec};
+use micrograd::nn::{LossFn, MLP, Module};
+use micrograd::optim::{Optimizer, SGD};
+use micrograd::pool::{GraphPool, PoolError};
+
+fn demo_data() -> (Vec<Vec<Value>>, Vec<Value>) {
+    let rows = [
+        [2.0, 3.0, -1.0],
+        [3.0, -1.0, 0.5],
+        [0.5, 1.0, 1.0],
+        [1.0, 1.0, -1.0],
+    ];
+    let xs = rows
+        .iter()
+        .map(|row| row.iter().map(|&x| Value::new(x)).collect())
+        .collect();
+    let ys = [1.0, -1.0, -1.0, 1.0].map(Value::new).to_vec();
+    (xs, ys)
+}
+
+#[test]
+fn test_pooled_training_matches_fresh_graphs() {
+    let (xs, ys) = demo_data();
+    let pooled = MLP::new(3, vec![4, 4, 1]);
+    let fresh = MLP::import_json(&pooled.export_json()).unwrap();
+    let mut pooled_opt = SGD::new(pooled.parameters(), 0.05);
+    let mut fresh_opt = SGD::new(fresh.parameters(), 0.05);
+
+    let pool = GraphPool::new(&pooled.batch_loss(&xs, &ys, LossFn::Mse)).unwrap();
+    let root = pool.root().clone();
+    for _ in 0..5 {
+        pool.refresh();
+        pool.root().backward();
+
+        fresh.zero_grad();
+        let loss = fresh.batch_loss(&xs, &ys, LossFn::Mse);
+        loss.backward();
+
+        assert_eq!(pool.root().data(), loss.data());
+        assert_eq!(
+            grad_vec(&pooled.parameters()),
+            grad_vec(&fresh.parameters())
+        );
+        pooled_opt.step();
+        fresh_opt.step();
+    }
+    // the same nodes were used throughout
+    assert_eq!(pool.root(), &root);
+}
+
+#[test]
+fn test_refresh_picks_up_new_leaf_data() {
+    let x = Value::new(1.0);
+    let w = Value::new(0.5);
+    let pool = GraphPool::new(&(x.clone() * w.clone()).tanh().powi(2)).unwrap();
+    assert_eq!(pool.num_nodes(), 5);
+
+    x.set_data(-2.0);
+    pool.refresh();
+    let expected = (-2.0_f64 * 0.5).tanh().powi(2);
+    assert!((pool.root().data() - expected).abs() < 1e-12);
+
+    // gradients follow the new data as well
+    pool.root().backward();
+    let x2 = Value::new(-2.0);
+    (x2.clone() * Value::new(0.5)).tanh().powi(2).backward();
+    assert!((x.grad() - x2.grad()).abs() < 1e-12);
+}
+
+#[test]
+fn test_pool_rejects_checkpoint() {
+    let x = Value::new(1.0);
+    let out = checkpoint(&[x], |xs| vec![xs[0].tanh()]);
+    assert!(matches!(
+        GraphPool::new(&out[0]),
+        Err(PoolError::UnsupportedOp("checkpoint"))
+    ));
+}
@@ -0,0 +1,122 @@
+use micrograd::engine::{NodeKind, Value, checkpoint, mul_add};
+use micrograd::serialize::{GraphError, SerializedGraph, from_json, to_json};
+use micrograd::trace_graph::print_tree;
+
+/// The graph from `draw_value_example`: o = tanh(x1 * w1 + x2 * w2 + b)
+fn value_example() -> Value {
+    let labeled = |data: f64, label: &str| {
+        let v = Value::new(data);
+        v.set_label(label.to_string());
+        v
+    };
+    let x1 = labeled(2.0, "x1");
+    let x2 = labeled(0.0, "x2");
+    let w1 = labeled(-3.0, "w1");
+    let w2 = labeled(1.0, "w2");
+    let b = labeled(6.881373587019543, "b");
+
+    let x1w1 = x1 * w1;
+    x1w1.set_label("x1 * w1".to_string());
+    let x2w2 = x2 * w2;
+    x2w2.set_label("x2 * w2".to_string());
+    let x1w1x2w2 = x1w1 + x2w2;
+    x1w1x2w2.set_label("x1 * w1 + x2 * w2".to_string());
+    let n = x1w1x2w2 + b;
+    n.set_label("n".to_string());
+    let o = n.tanh();
+    o.set_label("o".to_string());
+    o
+}
+
+#[test]
+fn test_roundtrip_value_example() {
+    let original = value_example();
+    let json = to_json(&original).unwrap();
+    let reloaded = from_json(&json).unwrap();
+
+    original.backward();
+    reloaded.backward();
+
+    // same structure, labels, data and gradients node for node
+    assert_eq!(print_tree(&reloaded), print_tree(&original));
+    assert_eq!(reloaded.data(), original.data());
+}
+
+#[test]
+fn test_roundtrip_keeps_op_args() {
+    let a = Value::new(1.5);
+    let root = a.powi(3).exp_clamped(2.0) + a.recip();
+    let reloaded = from_json(&to_json(&root).unwrap()).unwrap();
+
+    root.backward();
+    reloaded.backward();
+    assert_eq!(print_tree(&reloaded), print_tree(&root));
+
+    let graph = SerializedGraph::from_value(&root).unwrap();
+    let powi = graph
+        .nodes
+        .iter()
+        .find(|n| n.op.as_deref() == Some("powi"))
+        .unwrap();
+    assert_eq!(powi.op_arg, Some(3.0));
+}
+
+#[test]
+fn test_roundtrip_mul_add() {
+    let (a, b, c) = (Value::new(2.0), Value::new(-0.5), Value::new(0.25));
+    let root = mul_add(&a, &b, &c).tanh();
+    let reloaded = from_json(&to_json(&root).unwrap()).unwrap();
+
+    root.backward();
+    reloaded.backward();
+    assert_eq!(print_tree(&reloaded), print_tree(&root));
+}
+
+#[test]
+fn test_roundtrip_keeps_leaf_kinds() {
+    let scale = Value::constant(0.5);
+    let x = Value::new(2.0).as_input();
+    let w = Value::new(-3.0);
+    let root = scale * (x * w);
+    let reloaded = from_json(&to_json(&root).unwrap()).unwrap();
+    reloaded.backward();
+
+    let [scale, xw] = reloaded.prev().try_into().unwrap();
+    let [x, w] = xw.prev().try_into().unwrap();
+    assert_eq!(scale.kind(), NodeKind::Const);
+    assert_eq!(x.kind(), NodeKind::Input);
+    assert_eq!(w.kind(), NodeKind::Param);
+    assert_eq!(scale.grad(), 0.0);
+    assert_eq!(w.grad(), 1.0);
+}
+
+#[test]
+fn test_roundtrip_keeps_frozen_flags() {
+    let w = Value::new(-3.0);
+    w.set_frozen(true);
+    let root = w * Value::new(2.0);
+    let reloaded = from_json(&to_json(&root).unwrap()).unwrap();
+
+    let [w, x] = reloaded.prev().try_into().unwrap();
+    assert!(w.is_frozen());
+    assert!(!x.is_frozen());
+    assert!(!reloaded.is_frozen());
+}
+
+#[test]
+fn test_unsupported_op() {
+    let x = vec![Value::new(1.0)];
+    let out = checkpoint(&x, |x| vec![x[0].tanh()]);
+    assert!(matches!(
+        to_json(&out[0]),
+        Err(GraphError::UnsupportedOp(op)) if op == "checkpoint"
+    ));
+}
+
+#[test]
+fn test_recip_of_zero_is_an_error() {
+    let root = Value::new(2.0).recip();
+    let mut graph = SerializedGraph::from_value(&root).unwrap();
+    graph.nodes[0].data = 0.0;
+    assert!(matches!(graph.to_value(), Err(GraphError::RecipOfZero)));
+}
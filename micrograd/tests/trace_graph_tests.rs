@@ -0,0 +1,321 @@
+use micrograd::engine::{Value, checkpoint};
+use micrograd::nn::{MLP, mse_loss};
+use micrograd::trace_graph::{
+    DotOptions, RankDir, adjacency, grad_report, graph_diff, leaf_table, op_counts, print_tree,
+    render_svg, to_dot,
+};
+
+#[test]
+fn test_to_dot_contains_nodes() {
+    let a = Value::new(2.0);
+    a.set_label("a".to_string());
+    let b = Value::new(3.0);
+    b.set_label("b".to_string());
+    let c = a * b;
+
+    let dot = to_dot(&c, &DotOptions::default());
+    assert!(dot.starts_with("digraph trace_graph {"));
+    assert!(dot.contains("{ a | data 2.0000 | grad 0.0000 }"));
+    assert!(dot.contains("{ b | data 3.0000 | grad 0.0000 }"));
+    assert!(dot.contains("label=\"*\", shape=circle"));
+    assert!(!dot.contains("-> op0 [label="), "Expected no edge labels by default");
+    assert!(dot.contains("rankdir=LR;"));
+}
+
+#[test]
+fn test_to_dot_rankdir() {
+    let c = Value::new(2.0) * Value::new(3.0);
+
+    let options = DotOptions {
+        rankdir: RankDir::TB,
+        ..Default::default()
+    };
+    let dot = to_dot(&c, &options);
+    assert!(dot.contains("rankdir=TB;"));
+    assert!(!dot.contains("rankdir=LR;"));
+}
+
+#[test]
+fn test_to_dot_grad_flow() {
+    let a = Value::new(2.0);
+    let b = Value::new(3.0);
+    let c = a.clone() * b.clone();
+    c.backward();
+
+    let options = DotOptions {
+        show_grad_flow: true,
+        ..Default::default()
+    };
+    let dot = to_dot(&c, &options);
+    // ∂c/∂a = b = 3 flows into a, ∂c/∂b = a = 2 flows into b
+    assert!(dot.contains("n1 -> op0 [label=\"3.0000\"];"));
+    assert!(dot.contains("n2 -> op0 [label=\"2.0000\"];"));
+
+    // rendering must not disturb the accumulated gradients
+    assert_eq!(a.grad(), 3.0);
+    assert_eq!(b.grad(), 2.0);
+}
+
+#[test]
+fn test_to_dot_grad_flow_through_checkpoint_keeps_captured_grads() {
+    let x = Value::new(2.0);
+    let w = Value::new(0.5);
+    let captured = w.clone();
+    let out = checkpoint(std::slice::from_ref(&x), move |x| {
+        vec![(x[0].clone() * captured.clone()).tanh()]
+    });
+    out[0].backward();
+    let (x_grad, w_grad) = (x.grad(), w.grad());
+    assert_ne!(w_grad, 0.0);
+
+    let options = DotOptions {
+        show_grad_flow: true,
+        ..Default::default()
+    };
+    let dot = to_dot(&out[0], &options);
+    assert!(dot.contains(&format!("[label=\"{:.4}\"]", x_grad)));
+
+    assert_eq!(x.grad(), x_grad);
+    assert_eq!(w.grad(), w_grad);
+}
+
+#[test]
+fn test_grad_contributions() {
+    let a = Value::new(0.5);
+    let b = a.tanh();
+    b.backward();
+
+    let expected = 1.0 - b.data().powi(2);
+    let contributions = b.grad_contributions();
+    assert_eq!(contributions.len(), 1);
+    assert!((contributions[0] - expected).abs() < 1e-12);
+    assert!((a.grad() - expected).abs() < 1e-12);
+}
+
+#[test]
+fn test_print_tree() {
+    let a = Value::new(2.0);
+    a.set_label("a".to_string());
+    let b = Value::new(3.0);
+    b.set_label("b".to_string());
+    let c = a + b;
+    c.backward();
+
+    let expected = [
+        "#0 + | data 5.0000 | grad 1.0000",
+        "  #1 a | data 2.0000 | grad 1.0000",
+        "  #2 b | data 3.0000 | grad 1.0000",
+    ];
+    assert_eq!(print_tree(&c).lines().collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_print_tree_shared_node() {
+    let a = Value::new(2.0);
+    a.set_label("a".to_string());
+    let c = a.clone() * a;
+
+    let tree = print_tree(&c);
+    assert_eq!(tree.lines().count(), 3);
+    assert!(tree.contains("  #1 a | data 2.0000"));
+    assert!(tree.contains("  #1 a (see above)"));
+}
+
+#[test]
+fn test_adjacency() {
+    let a = Value::new(2.0);
+    let b = Value::new(-3.0);
+    let c = Value::new(10.0);
+    let ab = a.clone() * b.clone();
+    let root = ab.clone() + c.clone();
+
+    let (nodes, matrix) = adjacency(&root);
+    assert_eq!(nodes.len(), 5);
+    assert_eq!(nodes[0], root);
+    assert!(matrix.iter().all(|row| row.len() == 5));
+
+    for (i, parent) in nodes.iter().enumerate() {
+        let prev = parent.prev();
+        for (j, child) in nodes.iter().enumerate() {
+            assert_eq!(matrix[i][j], prev.contains(child), "edge {i} -> {j}");
+        }
+    }
+    let edges = matrix.iter().flatten().filter(|&&e| e).count();
+    assert_eq!(edges, 4);
+}
+
+#[test]
+fn test_op_counts_mlp_loss() {
+    let model = MLP::new(3, vec![4, 4, 1]);
+    let xs = [
+        [2.0, 3.0, -1.0],
+        [3.0, -1.0, 0.5],
+        [0.5, 1.0, 1.0],
+        [1.0, 1.0, -1.0],
+    ];
+    let preds: Vec<Value> = xs
+        .iter()
+        .map(|x| {
+            let x: Vec<Value> = x.iter().map(|&v| Value::new(v)).collect();
+            model.forward(&x)[0].clone()
+        })
+        .collect();
+    let targets: Vec<Value> = [1.0, -1.0, -1.0, 1.0].iter().map(|&v| Value::new(v)).collect();
+    let loss = mse_loss(&preds, &targets);
+
+    let counts = op_counts(&loss);
+    // one fused multiply-add per weight per sample (3*4 + 4*4 + 4*1 = 32)
+    assert_eq!(counts["mul_add"], 4 * 32);
+    // only the 1/n scaling is a plain product
+    assert_eq!(counts["*"], 1);
+    // one tanh per neuron per sample
+    assert_eq!(counts["tanh"], 4 * 9);
+    assert_eq!(counts["powi"], 4);
+    assert!(!counts.contains_key("exp"));
+}
+
+#[test]
+fn test_graph_diff() {
+    let product = || Value::new(2.0) * Value::new(3.0);
+
+    assert!(graph_diff(&product(), &product()).is_empty());
+
+    let sum = Value::new(2.0) + Value::new(3.0);
+    let diffs = graph_diff(&product(), &sum);
+    assert!(diffs.contains(&"node 0 op differs: * vs +".to_string()));
+    assert!(diffs.contains(&"node 0 data differs: 6 vs 5".to_string()));
+    assert_eq!(diffs.len(), 2);
+
+    let diffs = graph_diff(&product(), &Value::new(6.0));
+    assert!(diffs.contains(&"node count differs: 3 vs 1".to_string()));
+    assert!(diffs.contains(&"node 0 op differs: * vs leaf".to_string()));
+}
+
+#[test]
+fn test_leaf_table() {
+    let w = Value::new(-3.0);
+    w.set_label("w".to_string());
+    let x = Value::new(2.0);
+    x.set_label("x".to_string());
+    let b = Value::new(1.0);
+    b.set_label("b".to_string());
+    let y = (w * x + b).tanh();
+    y.backward();
+
+    let table = leaf_table(&y);
+    assert_eq!(table.len(), 3);
+    let row = |name: &str| {
+        table
+            .iter()
+            .find(|(label, _, _)| label.as_deref() == Some(name))
+            .cloned()
+            .unwrap()
+    };
+    // dy/d(wx + b) = 1 - tanh(-5)^2
+    let local = 1.0 - (-5.0_f64).tanh().powi(2);
+    let (_, data, grad) = row("w");
+    assert_eq!(data, -3.0);
+    assert!((grad - 2.0 * local).abs() < 1e-12);
+    let (_, data, grad) = row("x");
+    assert_eq!(data, 2.0);
+    assert!((grad + 3.0 * local).abs() < 1e-12);
+    let (_, data, grad) = row("b");
+    assert_eq!(data, 1.0);
+    assert!((grad - local).abs() < 1e-12);
+}
+
+#[test]
+fn test_leaf_table_skips_constants_and_inputs() {
+    let w = Value::new(0.5);
+    w.set_label("w".to_string());
+    let x = Value::new(2.0).as_input();
+    x.set_label("x".to_string());
+    let scale = Value::constant(3.0);
+    scale.set_label("scale".to_string());
+    let y = w * x * scale;
+    y.backward();
+
+    let table = leaf_table(&y);
+    assert_eq!(table, vec![(Some("w".to_string()), 0.5, 6.0)]);
+}
+
+#[test]
+fn test_to_dot_shapes_by_kind() {
+    let w = Value::new(2.0);
+    w.set_label("w".to_string());
+    let c = Value::constant(3.0);
+    c.set_label("c".to_string());
+    let x = Value::new(1.0).as_input();
+    x.set_label("x".to_string());
+    let y = w * c + x;
+
+    let dot = to_dot(&y, &DotOptions::default());
+    let node_line = |name: &str| {
+        dot.lines()
+            .find(|line| line.contains(&format!("{{ {} | data", name)))
+            .unwrap()
+            .to_string()
+    };
+    assert!(node_line("w").contains("shape=Mrecord"));
+    assert!(node_line("c").contains("shape=record, style=dashed"));
+    assert!(node_line("x").contains("fillcolor=lightblue"));
+}
+
+#[test]
+fn test_render_svg() {
+    let a = Value::new(2.0);
+    a.set_label("a <&> \"b\"".to_string());
+    let b = Value::new(-3.0);
+    let c = a.clone() * b;
+    let d = (c.clone() + a).tanh();
+    d.backward();
+
+    let svg = render_svg(&d);
+    assert!(svg.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\""));
+    assert!(svg.trim_end().ends_with("</svg>"));
+    // a, b, c, c + a and tanh
+    assert_eq!(svg.matches("<g class=\"node\">").count(), 5);
+    assert_eq!(svg.matches("</g>").count(), 5);
+    assert_eq!(svg.matches("class=\"edge\"").count(), 5);
+    assert!(svg.contains("a &lt;&amp;&gt; &quot;b&quot;"));
+    assert!(!svg.contains("<&>"));
+    assert!(svg.contains(">tanh</text>"));
+}
+
+#[test]
+fn test_grad_report_lists_labeled_nodes() {
+    // the graph from `draw_value_example`: o = tanh(x1 * w1 + x2 * w2 + b)
+    let labeled = |data: f64, label: &str| {
+        let v = Value::new(data);
+        v.set_label(label.to_string());
+        v
+    };
+    let x1 = labeled(2.0, "x1");
+    let x2 = labeled(0.0, "x2");
+    let w1 = labeled(-3.0, "w1");
+    let w2 = labeled(1.0, "w2");
+    let b = labeled(6.881373587019543, "b");
+    let n = x1 * w1 + x2 * w2 + b;
+    n.set_label("n".to_string());
+    let o = n.tanh();
+    o.set_label("o".to_string());
+    o.backward();
+
+    let report = grad_report(&o);
+    let rows: Vec<Vec<&str>> = report
+        .lines()
+        .skip(2)
+        .map(|line| line.split('|').map(str::trim).collect())
+        .collect();
+    // unlabeled intermediate nodes are left out, the rest in print_tree order
+    let labels: Vec<&str> = rows.iter().map(|r| r[0]).collect();
+    assert_eq!(labels, vec!["o", "n", "x1", "w1", "x2", "w2", "b"]);
+
+    let row = |label: &str| rows.iter().find(|r| r[0] == label).unwrap();
+    assert_eq!(row("o")[1..], ["0.7071", "1.0000"]);
+    assert_eq!(row("x1")[1..], ["2.0000", "-1.5000"]);
+    assert_eq!(row("w1")[1..], ["-3.0000", "1.0000"]);
+    assert_eq!(row("x2")[1..], ["0.0000", "0.5000"]);
+    assert_eq!(row("w2")[1..], ["1.0000", "0.0000"]);
+    assert!(report.starts_with("label |    data |    grad\n"));
+}
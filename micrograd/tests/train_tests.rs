@@ -0,0 +1,290 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use micrograd::engine::Value;
+use micrograd::nn::{LossFn, MLP, Module, huber_loss, mse_loss};
+use micrograd::optim::{Adam, AdamW, Optimizer, SGD, unscale_grads};
+use micrograd::train::Trainer;
+
+fn demo_data() -> (Vec<Vec<Value>>, Vec<Value>) {
+    let xs = vec![
+        vec![Value::new(2.0), Value::new(3.0), Value::new(-1.0)],
+        vec![Value::new(3.0), Value::new(-1.0), Value::new(0.5)],
+        vec![Value::new(0.5), Value::new(1.0), Value::new(1.0)],
+        vec![Value::new(1.0), Value::new(1.0), Value::new(-1.0)],
+    ];
+    let ys = vec![
+        Value::new(1.0),
+        Value::new(-1.0),
+        Value::new(-1.0),
+        Value::new(1.0),
+    ];
+    (xs, ys)
+}
+
+#[test]
+fn test_fit_returns_loss_per_epoch() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+
+    let optimizer = SGD::new(mlp.parameters(), 0.05);
+    let losses = Trainer::new(optimizer, 10).fit(&mlp, &xs, &ys);
+    assert_eq!(losses.len(), 10);
+    assert!(losses.iter().all(|l| l.is_finite()));
+}
+
+#[test]
+fn test_lr_find() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let initial: Vec<f64> = mlp.parameters().iter().map(|p| p.data()).collect();
+
+    let mut trainer = Trainer::new(SGD::new(mlp.parameters(), 0.0), 5);
+    let results = trainer.lr_find(&mlp, &xs, &ys, (1e-4, 1.0), 5);
+
+    assert_eq!(results.len(), 5);
+    assert!((results[0].0 - 1e-4).abs() < 1e-12);
+    assert!((results[4].0 - 1.0).abs() < 1e-9);
+    for window in results.windows(2) {
+        assert!(window[1].0 > window[0].0, "Expected increasing learning rates");
+    }
+    assert!(results.iter().all(|(_, loss)| loss.is_finite()));
+
+    let restored: Vec<f64> = mlp.parameters().iter().map(|p| p.data()).collect();
+    assert_eq!(initial, restored, "Expected lr_find to restore the model");
+    assert_eq!(trainer.optimizer().lr(), 0.0);
+}
+
+#[test]
+fn test_on_epoch_end_callback() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let recorded = Rc::new(RefCell::new(Vec::new()));
+
+    let mut trainer = Trainer::new(SGD::new(mlp.parameters(), 0.05), 4);
+    let sink = Rc::clone(&recorded);
+    trainer.on_epoch_end(move |epoch, loss| sink.borrow_mut().push((epoch, loss)));
+    let losses = trainer.fit(&mlp, &xs, &ys);
+
+    let recorded = recorded.borrow();
+    assert_eq!(recorded.len(), 4, "Expected one call per epoch");
+    for (i, &(epoch, loss)) in recorded.iter().enumerate() {
+        assert_eq!(epoch, i);
+        assert_eq!(loss, losses[i]);
+    }
+}
+
+/// Trains a fresh copy of the same network with whichever optimizer `make` builds
+fn train_with<O: Optimizer>(make: impl Fn(Vec<Value>) -> O) -> Vec<f64> {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    Trainer::new(make(mlp.parameters()), 30).fit(&mlp, &xs, &ys)
+}
+
+#[test]
+fn test_trainer_with_swapped_optimizers() {
+    let sgd = train_with(|params| SGD::new(params, 0.05));
+    let adam = train_with(|params| Adam::new(params, 0.05));
+    let adamw = train_with(|params| AdamW::new(params, 0.05, 0.01));
+
+    for losses in [sgd, adam, adamw] {
+        assert_eq!(losses.len(), 30);
+        assert!(
+            losses[29] < losses[0],
+            "Expected loss to decrease, got {} -> {}",
+            losses[0],
+            losses[29]
+        );
+    }
+}
+
+#[test]
+fn test_optimizer_zero_grad() {
+    let a = Value::new(2.0);
+    let optimizer = SGD::new(vec![a.clone()], 0.1);
+    (a.clone() * Value::new(3.0)).backward();
+    assert_eq!(a.grad(), 3.0);
+
+    optimizer.zero_grad();
+    assert_eq!(a.grad(), 0.0);
+}
+
+#[test]
+fn test_param_groups_step_by_their_lr() {
+    let base = [Value::new(1.0), Value::new(-2.0)];
+    let slow = [Value::new(1.0), Value::new(-2.0)];
+    let mut optimizer = SGD::new(base.to_vec(), 0.1);
+    optimizer.add_group(slow.to_vec(), 0.01);
+
+    // every parameter gets the same gradient of 2
+    let loss = base
+        .iter()
+        .chain(&slow)
+        .map(|p| p.clone() * Value::new(2.0))
+        .reduce(|acc, x| acc + x)
+        .unwrap();
+    loss.backward();
+    optimizer.step();
+
+    // each group moves by its own lr times the gradient
+    for (p, start) in base.iter().zip([1.0, -2.0]) {
+        assert!((start - p.data() - 0.2).abs() < 1e-12);
+    }
+    for (p, start) in slow.iter().zip([1.0, -2.0]) {
+        assert!((start - p.data() - 0.02).abs() < 1e-12);
+    }
+
+    // set_lr only changes the base group
+    optimizer.set_lr(0.0);
+    optimizer.step();
+    assert!((base[0].data() - 0.8).abs() < 1e-12);
+    assert!((slow[0].data() - 0.96).abs() < 1e-12);
+}
+
+#[test]
+fn test_adam_first_step_moves_by_lr() {
+    // with bias correction the first Adam step is lr * sign(grad)
+    let a = Value::new(1.0);
+    let mut optimizer = Adam::new(vec![a.clone()], 0.1);
+    (a.clone() * Value::new(5.0)).backward();
+    optimizer.step();
+    assert!((a.data() - 0.9).abs() < 1e-6);
+}
+
+#[test]
+fn test_scaled_backward_then_unscale_matches_plain() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let loss = || {
+        let preds: Vec<Value> = xs.iter().flat_map(|x| mlp.forward(x)).collect();
+        mse_loss(&preds, &ys)
+    };
+
+    loss().backward();
+    let plain: Vec<f64> = mlp.parameters().iter().map(Value::grad).collect();
+
+    let scale = 1024.0;
+    mlp.zero_grad();
+    loss().backward_scaled(scale);
+    unscale_grads(&mlp.parameters(), scale);
+
+    for (param, expected) in mlp.parameters().iter().zip(plain) {
+        assert!((param.grad() - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_batch_loss_matches_manual_mse() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+
+    let loss = mlp.batch_loss(&xs, &ys, LossFn::Mse);
+    let y_pred = xs.iter().flat_map(|x| mlp.forward(x)).collect::<Vec<_>>();
+    let manual = ys
+        .iter()
+        .zip(y_pred.iter())
+        .map(|(y, y_hat)| (y.clone() - y_hat.clone()).powi(2))
+        .reduce(|acc, x| acc + x)
+        .unwrap();
+    assert!((loss.data() - manual.data() / 4.0).abs() < 1e-12);
+    assert!((loss.data() - mse_loss(&y_pred, &ys).data()).abs() < 1e-12);
+
+    loss.backward();
+    let grads: Vec<f64> = mlp.parameters().iter().map(Value::grad).collect();
+    assert!(grads.iter().any(|&g| g != 0.0));
+
+    mlp.zero_grad();
+    mse_loss(&y_pred, &ys).backward();
+    for (param, expected) in mlp.parameters().iter().zip(grads) {
+        assert!((param.grad() - expected).abs() < 1e-12);
+    }
+}
+
+#[test]
+fn test_batch_loss_huber() {
+    let (xs, ys) = demo_data();
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+
+    let loss = mlp.batch_loss(&xs, &ys, LossFn::Huber(0.5));
+    let expected: f64 = xs
+        .iter()
+        .zip(&ys)
+        .map(|(x, y)| huber_loss(&mlp.forward(x)[0], y, 0.5).data())
+        .sum::<f64>()
+        / 4.0;
+    assert!((loss.data() - expected).abs() < 1e-12);
+}
+
+/// A fresh network with the same parameters as `mlp`
+fn copy_of(mlp: &MLP) -> MLP {
+    let copy = MLP::new(3, vec![4, 4, 1]);
+    for (dst, src) in copy.parameters().iter().zip(mlp.parameters()) {
+        dst.set_data(src.data());
+    }
+    copy
+}
+
+/// Parameter data and gradients after one epoch of SGD with the trainer `make` builds
+fn one_epoch(mlp: &MLP, make: impl Fn(SGD) -> Trainer<SGD>) -> Vec<(f64, f64)> {
+    let (xs, ys) = demo_data();
+    make(SGD::new(mlp.parameters(), 0.05)).fit(mlp, &xs, &ys);
+    mlp.parameters().iter().map(|p| (p.data(), p.grad())).collect()
+}
+
+#[test]
+fn test_grad_noise_is_seeded() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+    let noisy = |seed| move |opt| Trainer::new(opt, 1).with_grad_noise(0.1, 0.55, seed);
+
+    let a = one_epoch(&copy_of(&mlp), noisy(7));
+    let b = one_epoch(&copy_of(&mlp), noisy(7));
+    let c = one_epoch(&copy_of(&mlp), noisy(8));
+    let plain = one_epoch(&copy_of(&mlp), |opt| Trainer::new(opt, 1));
+
+    assert_eq!(a, b);
+    assert_ne!(a, c);
+    assert_ne!(a, plain);
+}
+
+#[test]
+fn test_grad_noise_zero_std_is_noop() {
+    let mlp = MLP::new(3, vec![4, 4, 1]);
+
+    let plain = one_epoch(&copy_of(&mlp), |opt| Trainer::new(opt, 1));
+    let silent = one_epoch(&copy_of(&mlp), |opt| {
+        Trainer::new(opt, 1).with_grad_noise(0.0, 0.55, 7)
+    });
+    assert_eq!(plain, silent);
+}
+
+#[test]
+fn test_frozen_layers_are_skipped_by_optimizers() {
+    let (xs, ys) = demo_data();
+    let data = |mlp: &MLP, i: usize| -> Vec<f64> {
+        mlp.layers()[i].parameters().iter().map(Value::data).collect()
+    };
+
+    for kind in ["sgd", "adam", "adamw"] {
+        let mlp = MLP::new(3, vec![4, 4, 1]);
+        mlp.freeze_layers(..2);
+        let before: Vec<Vec<f64>> = (0..3).map(|i| data(&mlp, i)).collect();
+
+        let mut optimizer: Box<dyn Optimizer> = match kind {
+            "sgd" => Box::new(SGD::new(mlp.parameters(), 0.1)),
+            "adam" => Box::new(Adam::new(mlp.parameters(), 0.1)),
+            _ => Box::new(AdamW::new(mlp.parameters(), 0.1, 0.01)),
+        };
+        mlp.batch_loss(&xs, &ys, LossFn::Mse).backward();
+        // frozen parameters still receive gradients
+        assert!(mlp.layers()[0].parameters().iter().any(|p| p.grad() != 0.0));
+        optimizer.step();
+
+        assert_eq!(data(&mlp, 0), before[0]);
+        assert_eq!(data(&mlp, 1), before[1]);
+        assert_ne!(data(&mlp, 2), before[2]);
+
+        mlp.unfreeze_layers(..);
+        optimizer.step();
+        assert_ne!(data(&mlp, 0), before[0]);
+    }
+}